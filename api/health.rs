@@ -0,0 +1,18 @@
+use vercel_runtime::{Body, Error, Request, Response};
+use blog_search_service::http;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    vercel_runtime::run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    // 预检请求按配置的 CORS 策略处理，不打开索引
+    let vercel = http::VercelAdapter::new(&req);
+    if req.method() == "OPTIONS" {
+        return vercel.preflight();
+    }
+
+    // `/health/live` 不打开索引；`/health/ready` 打开索引并检查文档数
+    vercel.health("./data/search_index")
+}
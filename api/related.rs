@@ -0,0 +1,24 @@
+use vercel_runtime::{Body, Error, Request, Response};
+use blog_search_service::http;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    vercel_runtime::run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    // 预检请求按配置的 CORS 策略处理，不打开索引
+    let vercel = http::VercelAdapter::new(&req);
+    if req.method() == "OPTIONS" {
+        return vercel.preflight();
+    }
+    let ctx = vercel.context();
+
+    // 加载配置并打开索引
+    let service = match http::SearchService::open("./data/search_index") {
+        Ok(service) => service,
+        Err(e) => return vercel.respond(ctx.error(&e)),
+    };
+
+    vercel.respond(service.related(&ctx, req.uri().query()))
+}
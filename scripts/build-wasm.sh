@@ -0,0 +1,11 @@
+#!/bin/bash
+set -e
+
+# 构建浏览器端搜索模块，输出到 static/pkg
+# 需要 wasm-pack: cargo install wasm-pack
+rustup target add wasm32-unknown-unknown
+wasm-pack build --target web --release --out-dir static/pkg --no-pack \
+    -- --no-default-features --features wasm
+
+# 导出静态索引到 static/search
+cargo run --bin indexer --release -- export static/search --shard
@@ -0,0 +1,280 @@
+//! 文本分析（分词、停用词与词干提取）
+//!
+//! 这里的分析器不依赖 tantivy，索引端通过 `tokenizer` 模块把它们注册为
+//! tantivy 分词器，保证索引与查询使用完全相同的切分规则。
+//!
+//! - CJK 分析器：拉丁字母与数字按单词切分并转为小写，连续的 CJK 字符切分为
+//!   相互重叠的二元组（`搜索引擎` -> `搜索`、`索引`、`引擎`）。
+//! - 英文分析器：在 CJK 分析器的切分结果上，对拉丁单词去除停用词并做
+//!   Snowball 词干提取（`indexing` -> `index`）。
+
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 超过该字节长度的词项会被丢弃
+const MAX_TOKEN_BYTES: usize = 40;
+/// 含 CJK 字符比例不低于该值的文本被判定为中文
+const CJK_RATIO_THRESHOLD: f32 = 0.05;
+
+/// 默认的英文停用词
+pub const DEFAULT_ENGLISH_STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "can", "do", "does",
+    "for", "from", "how", "if", "in", "into", "is", "it", "its", "no", "not", "of",
+    "on", "or", "so", "such", "that", "the", "their", "then", "there", "these",
+    "they", "this", "to", "was", "what", "when", "where", "which", "who", "why",
+    "will", "with",
+];
+
+/// 文档语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// 中文（也是无法识别时的默认语言，使用 CJK 分析器）
+    #[default]
+    Zh,
+    /// 英文
+    En,
+}
+
+impl Language {
+    /// 语言代码
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::Zh => "zh",
+            Language::En => "en",
+        }
+    }
+
+    /// 解析语言代码，支持 `en-US`、`zh_CN` 之类的地区后缀
+    pub fn from_code(code: &str) -> Option<Language> {
+        let code = code.trim().to_lowercase();
+        let primary = code.split(['-', '_']).next().unwrap_or("");
+        match primary {
+            "zh" => Some(Language::Zh),
+            "en" => Some(Language::En),
+            _ => None,
+        }
+    }
+}
+
+/// 根据 CJK 字符所占比例识别文本语言
+pub fn detect_language(text: &str) -> Language {
+    let mut cjk = 0usize;
+    let mut latin = 0usize;
+    for c in text.chars() {
+        if is_cjk(c) {
+            cjk += 1;
+        } else if c.is_alphabetic() {
+            latin += 1;
+        }
+    }
+
+    if latin == 0 || cjk as f32 / (cjk + latin) as f32 >= CJK_RATIO_THRESHOLD {
+        Language::Zh
+    } else {
+        Language::En
+    }
+}
+
+/// 分析得到的词项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyzedToken {
+    /// 归一化后的词项
+    pub text: String,
+    /// 在原文中的起始字节偏移
+    pub offset_from: usize,
+    /// 在原文中的结束字节偏移
+    pub offset_to: usize,
+    /// 词项位置
+    pub position: usize,
+}
+
+/// 英文分析器配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnglishAnalyzerConfig {
+    /// 停用词（不区分大小写）
+    pub stop_words: Vec<String>,
+    /// 是否启用 Snowball 词干提取
+    pub stemming: bool,
+}
+
+impl Default for EnglishAnalyzerConfig {
+    fn default() -> Self {
+        EnglishAnalyzerConfig {
+            stop_words: DEFAULT_ENGLISH_STOP_WORDS.iter().map(|w| w.to_string()).collect(),
+            stemming: true,
+        }
+    }
+}
+
+/// 分析器配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalysisConfig {
+    /// 英文分析器配置
+    pub english: EnglishAnalyzerConfig,
+}
+
+/// 按语言选择的文本分析器
+#[derive(Debug, Clone)]
+pub enum Analyzer {
+    /// CJK 二元组 + 小写拉丁单词
+    Cjk,
+    /// 小写、停用词、词干提取
+    English {
+        stop_words: HashSet<String>,
+        stemming: bool,
+    },
+}
+
+impl Analyzer {
+    /// 创建英文分析器
+    pub fn english(config: &EnglishAnalyzerConfig) -> Analyzer {
+        Analyzer::English {
+            stop_words: config.stop_words.iter().map(|w| w.to_lowercase()).collect(),
+            stemming: config.stemming,
+        }
+    }
+
+    /// 为指定语言创建分析器
+    pub fn for_language(language: Language, config: &AnalysisConfig) -> Analyzer {
+        match language {
+            Language::Zh => Analyzer::Cjk,
+            Language::En => Analyzer::english(&config.english),
+        }
+    }
+
+    /// 分析文本
+    pub fn analyze(&self, text: &str) -> Vec<AnalyzedToken> {
+        match self {
+            Analyzer::Cjk => segment(text),
+            Analyzer::English { stop_words, stemming } => {
+                let stemmer = stemming.then(|| Stemmer::create(Algorithm::English));
+                segment(text)
+                    .into_iter()
+                    .filter(|token| !stop_words.contains(&token.text))
+                    .map(|mut token| {
+                        if let Some(stemmer) = &stemmer {
+                            if token.text.chars().all(|c| c.is_ascii_alphabetic()) {
+                                token.text = stemmer.stem(&token.text).into_owned();
+                            }
+                        }
+                        token
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// 基础切分：拉丁单词转小写，CJK 连续字符切分为二元组
+///
+/// 单独出现的 CJK 字符保留为一元组；位置按输出顺序递增。
+fn segment(text: &str) -> Vec<AnalyzedToken> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if is_cjk(c) {
+            let mut run = Vec::new();
+            while let Some(&(i, c)) = chars.peek() {
+                if !is_cjk(c) {
+                    break;
+                }
+                run.push((i, c.len_utf8()));
+                chars.next();
+            }
+            if run.len() == 1 {
+                let (i, len) = run[0];
+                push_token(&mut tokens, &text[i..i + len], i, i + len);
+            } else {
+                for pair in run.windows(2) {
+                    let (from, _) = pair[0];
+                    let (i, len) = pair[1];
+                    push_token(&mut tokens, &text[from..i + len], from, i + len);
+                }
+            }
+        } else if c.is_alphanumeric() {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !c.is_alphanumeric() || is_cjk(c) {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let word = &text[start..end];
+            if word.len() <= MAX_TOKEN_BYTES {
+                push_token(&mut tokens, &word.to_lowercase(), start, end);
+            }
+        } else {
+            chars.next();
+        }
+    }
+
+    tokens
+}
+
+fn push_token(tokens: &mut Vec<AnalyzedToken>, text: &str, offset_from: usize, offset_to: usize) {
+    let position = tokens.len();
+    tokens.push(AnalyzedToken {
+        text: text.to_string(),
+        offset_from,
+        offset_to,
+        position,
+    });
+}
+
+/// 是否为 CJK 字符（汉字、假名、谚文）
+pub fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF      // 平假名、片假名
+        | 0x3400..=0x4DBF    // CJK 扩展 A
+        | 0x4E00..=0x9FFF    // CJK 统一汉字
+        | 0xAC00..=0xD7AF    // 谚文音节
+        | 0xF900..=0xFAFF    // CJK 兼容汉字
+        | 0x20000..=0x2A6DF  // CJK 扩展 B
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(tokens: &[AnalyzedToken]) -> Vec<&str> {
+        tokens.iter().map(|t| t.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_cjk_bigrams_and_latin_words() {
+        let tokens = Analyzer::Cjk.analyze("Rust搜索引擎, 是 Tantivy");
+        assert_eq!(texts(&tokens), ["rust", "搜索", "索引", "引擎", "是", "tantivy"]);
+        assert_eq!(&"Rust搜索引擎"[tokens[1].offset_from..tokens[1].offset_to], "搜索");
+        assert_eq!(tokens[5].position, 5);
+    }
+
+    #[test]
+    fn test_english_stop_words_and_stemming() {
+        let analyzer = Analyzer::english(&EnglishAnalyzerConfig::default());
+        let tokens = analyzer.analyze("How to index the Indexing docs");
+        assert_eq!(texts(&tokens), ["index", "index", "doc"]);
+
+        let analyzer = Analyzer::english(&EnglishAnalyzerConfig {
+            stop_words: vec!["docs".to_string()],
+            stemming: false,
+        });
+        assert_eq!(texts(&analyzer.analyze("How to index docs")), ["how", "to", "index"]);
+    }
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(detect_language("Error handling in Rust"), Language::En);
+        assert_eq!(detect_language("这是一篇测试文章，用来测试 search 功能"), Language::Zh);
+        assert_eq!(detect_language("12345"), Language::Zh);
+        assert_eq!(Language::from_code("en-US"), Some(Language::En));
+        assert_eq!(Language::from_code("zh_CN"), Some(Language::Zh));
+        assert_eq!(Language::from_code("fr"), None);
+    }
+}
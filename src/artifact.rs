@@ -0,0 +1,251 @@
+//! 供浏览器端离线搜索使用的静态索引文件
+//!
+//! 索引器把整个索引导出为一个带版本号的 JSON 文件（或一个清单加若干分片），
+//! 包含文档元数据、按语言划分的倒排表以及计算 BM25 所需的字段长度。
+//! 词项由 `analysis` 中与服务端相同的分析器生成。
+//!
+//! 倒排表只记录词频，不记录位置，因此静态搜索不支持短语和邻近查询。
+//!
+//! 分片时按词项首字符划分：拉丁字母和数字各自一片，其余字符按 Unicode
+//! 码位每 256 个一片（`u4e` 覆盖 U+4E00..U+4EFF）。清单中的 `shards`
+//! 列出所有分片键，查询时只需下载查询词所在的分片。
+
+use crate::analysis::{AnalysisConfig, Analyzer, Language};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 静态索引格式版本，格式不兼容时递增
+pub const ARTIFACT_VERSION: u32 = 1;
+/// 清单（或未分片时的完整索引）文件名
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// 语言代码 -> 词项 -> 倒排表
+pub type Postings = BTreeMap<String, BTreeMap<String, Vec<Posting>>>;
+
+/// 倒排表中的一项：`[文档序号, 标题词频, 正文词频]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Posting(pub u32, pub u32, pub u32);
+
+impl Posting {
+    /// 文档在 `docs` 中的序号
+    pub fn doc(&self) -> usize {
+        self.0 as usize
+    }
+
+    /// 标题中的词频
+    pub fn title_tf(&self) -> u32 {
+        self.1
+    }
+
+    /// 正文中的词频
+    pub fn content_tf(&self) -> u32 {
+        self.2
+    }
+}
+
+/// 静态索引中的文档
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactDoc {
+    pub path: String,
+    pub title: String,
+    pub excerpt: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub lang: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// 标题和正文的词元数
+    #[serde(default)]
+    pub field_lens: [u32; 2],
+}
+
+/// 静态索引
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchArtifact {
+    /// 格式版本，见 [`ARTIFACT_VERSION`]
+    pub version: u32,
+    /// 生成词项时使用的分析器配置，查询端必须使用相同的配置
+    pub analysis: AnalysisConfig,
+    pub docs: Vec<ArtifactDoc>,
+    /// 各语言标题和正文的词元总数，用于计算平均字段长度
+    pub field_tokens: BTreeMap<String, [u64; 2]>,
+    /// 分片键；为空时倒排表全部内嵌在 `postings` 中
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shards: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub postings: Postings,
+}
+
+/// 一个分片
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactShard {
+    pub version: u32,
+    pub postings: Postings,
+}
+
+impl SearchArtifact {
+    /// 把倒排表拆成分片，返回分片键到分片的映射，并在清单中记录分片键
+    pub fn split_shards(&mut self) -> BTreeMap<String, ArtifactShard> {
+        let mut shards: BTreeMap<String, ArtifactShard> = BTreeMap::new();
+        for (lang, terms) in std::mem::take(&mut self.postings) {
+            for (term, postings) in terms {
+                shards
+                    .entry(shard_key(&term))
+                    .or_insert_with(|| ArtifactShard {
+                        version: self.version,
+                        postings: Postings::new(),
+                    })
+                    .postings
+                    .entry(lang.clone())
+                    .or_default()
+                    .insert(term, postings);
+            }
+        }
+        self.shards = shards.keys().cloned().collect();
+        shards
+    }
+
+    /// 合并已下载的分片
+    pub fn merge_shard(&mut self, shard: ArtifactShard) {
+        for (lang, terms) in shard.postings {
+            self.postings.entry(lang).or_default().extend(terms);
+        }
+    }
+
+    /// 某种语言中词项的倒排表
+    pub fn postings_of(&self, language: Language, term: &str) -> Option<&[Posting]> {
+        self.postings
+            .get(language.code())
+            .and_then(|terms| terms.get(term))
+            .map(Vec::as_slice)
+    }
+}
+
+/// 词项所在分片的键
+pub fn shard_key(term: &str) -> String {
+    match term.chars().next() {
+        Some(c) if c.is_ascii_alphanumeric() => c.to_ascii_lowercase().to_string(),
+        Some(c) => format!("u{:x}", c as u32 >> 8),
+        None => "_".to_string(),
+    }
+}
+
+/// 分片文件名
+pub fn shard_file(key: &str) -> String {
+    format!("shard-{}.json", key)
+}
+
+/// 静态索引构建器
+pub struct ArtifactBuilder {
+    analysis: AnalysisConfig,
+    docs: Vec<(ArtifactDoc, String)>,
+}
+
+impl ArtifactBuilder {
+    pub fn new(analysis: AnalysisConfig) -> Self {
+        ArtifactBuilder {
+            analysis,
+            docs: Vec::new(),
+        }
+    }
+
+    /// 加入一篇文档；`doc.lang` 决定使用的分析器
+    pub fn add_document(&mut self, doc: ArtifactDoc, content: &str) {
+        self.docs.push((doc, content.to_string()));
+    }
+
+    /// 分析所有文档并生成静态索引，文档按路径排序
+    pub fn build(mut self) -> SearchArtifact {
+        self.docs.sort_by(|a, b| a.0.path.cmp(&b.0.path));
+
+        let mut docs = Vec::with_capacity(self.docs.len());
+        let mut field_tokens: BTreeMap<String, [u64; 2]> = BTreeMap::new();
+        let mut postings = Postings::new();
+
+        for (id, (mut doc, content)) in self.docs.into_iter().enumerate() {
+            let language = Language::from_code(&doc.lang).unwrap_or_default();
+            let analyzer = Analyzer::for_language(language, &self.analysis);
+
+            let mut frequencies: BTreeMap<String, [u32; 2]> = BTreeMap::new();
+            for (field, text) in [doc.title.as_str(), content.as_str()].into_iter().enumerate() {
+                let tokens = analyzer.analyze(text);
+                doc.field_lens[field] = tokens.len() as u32;
+                field_tokens.entry(language.code().to_string()).or_default()[field] += tokens.len() as u64;
+                for token in tokens {
+                    frequencies.entry(token.text).or_default()[field] += 1;
+                }
+            }
+
+            let terms = postings.entry(language.code().to_string()).or_default();
+            for (term, [title_tf, content_tf]) in frequencies {
+                terms.entry(term).or_default().push(Posting(id as u32, title_tf, content_tf));
+            }
+            docs.push(doc);
+        }
+
+        SearchArtifact {
+            version: ARTIFACT_VERSION,
+            analysis: self.analysis,
+            docs,
+            field_tokens,
+            shards: Vec::new(),
+            postings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> SearchArtifact {
+        let mut builder = ArtifactBuilder::new(AnalysisConfig::default());
+        builder.add_document(
+            ArtifactDoc {
+                path: "b.md".to_string(),
+                title: "Indexing Errors".to_string(),
+                lang: "en".to_string(),
+                ..ArtifactDoc::default()
+            },
+            "How errors are indexed",
+        );
+        builder.add_document(
+            ArtifactDoc {
+                path: "a.md".to_string(),
+                title: "搜索引擎".to_string(),
+                lang: "zh".to_string(),
+                ..ArtifactDoc::default()
+            },
+            "中文搜索",
+        );
+        builder.build()
+    }
+
+    #[test]
+    fn test_build_postings() {
+        let artifact = sample();
+        assert_eq!(artifact.docs[0].path, "a.md");
+        assert_eq!(artifact.docs[1].field_lens, [2, 2]);
+        assert_eq!(artifact.postings_of(Language::En, "index"), Some(&[Posting(1, 1, 1)][..]));
+        assert_eq!(artifact.postings_of(Language::Zh, "搜索"), Some(&[Posting(0, 1, 1)][..]));
+        assert_eq!(artifact.postings_of(Language::En, "how"), None);
+        assert_eq!(artifact.field_tokens["zh"], [3, 3]);
+    }
+
+    #[test]
+    fn test_shards_round_trip() {
+        let mut artifact = sample();
+        let original = artifact.postings.clone();
+        let shards = artifact.split_shards();
+        assert!(artifact.postings.is_empty());
+        assert_eq!(artifact.shards, ["e", "i", "u4e", "u5f", "u64", "u65", "u7d"]);
+        assert_eq!(shard_key("Rust"), "r");
+        assert_eq!(shard_file("u4e"), "shard-u4e.json");
+
+        for (_, shard) in shards {
+            let json = serde_json::to_string(&shard).unwrap();
+            artifact.merge_shard(serde_json::from_str(&json).unwrap());
+        }
+        assert_eq!(artifact.postings, original);
+    }
+}
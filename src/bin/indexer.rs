@@ -1,20 +1,79 @@
 use anyhow::Result;
-use blog_search_service::SearchEngine;
+use blog_search_service::artifact::{shard_file, MANIFEST_FILE};
+use blog_search_service::popularity::Popularity;
+use blog_search_service::query_log::{self, QueryReport};
+use blog_search_service::watch::{ContentWatcher, DEFAULT_DEBOUNCE};
+use blog_search_service::{AnalysisConfig, SearchEngine, SearchError, ServiceConfig, DEFAULT_RELATED_LIMIT};
+use serde_json::json;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use walkdir::WalkDir;
 
+const USAGE: &str = "用法:
+  indexer [build]                 从 content/blog 重建索引
+  indexer explain <查询> <路径>   解释某篇文章在查询下的得分
+  indexer related [输出文件]      预先计算每篇文章的相关文章，默认写入 static/related.json
+  indexer export [目录] [--shard] 导出供浏览器离线搜索的静态索引，默认写入 static/search/
+  indexer queries [--days N] [--top N] [--json]
+                                  根据查询日志统计最近 N 天（默认 7）的热门查询、无结果查询和趋势词
+  indexer popularity              汇总查询日志中的点击，生成文章热度文件（之后需要重建索引）
+  indexer watch [--debounce-ms N] 同步索引后监视 content/blog，文章保存后 N 毫秒（默认 500）内更新索引";
+
+/// 查询报告默认统计的天数
+const DEFAULT_REPORT_DAYS: u32 = 7;
+/// 查询报告每个列表默认的条数
+const DEFAULT_REPORT_TOP: usize = 20;
+
+/// 相关文章文件的格式版本
+const RELATED_FORMAT_VERSION: u32 = 1;
+
 fn get_content_path() -> PathBuf {
     let mut content_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     content_path.pop(); // 回到项目根目录
     content_path.join("content").join("blog")
 }
 
-fn main() -> Result<()> {
+fn get_index_path() -> PathBuf {
     // 使用绝对路径
-    let index_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .join("data")
-        .join("search_index");
+        .join("search_index")
+}
+
+fn get_static_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("static")
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None | Some("build") => build_index(),
+        Some("explain") => match &args[1..] {
+            [query, path] => explain(query, path),
+            _ => Err(anyhow::anyhow!("explain 需要查询和路径两个参数\n\n{}", USAGE)),
+        },
+        Some("related") => match &args[1..] {
+            [] => export_related(&get_static_path().join("related.json")),
+            [output] => export_related(Path::new(output)),
+            _ => Err(anyhow::anyhow!("related 最多接受一个输出文件参数\n\n{}", USAGE)),
+        },
+        Some("export") => export_artifact(&args[1..]),
+        Some("queries") => query_report(&args[1..]),
+        Some("popularity") => export_popularity(),
+        Some("watch") => watch(&args[1..]),
+        Some("help" | "-h" | "--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => Err(anyhow::anyhow!("未知命令: {}\n\n{}", command, USAGE)),
+    }
+}
+
+fn build_index() -> Result<()> {
+    let index_path = get_index_path();
     let content_path = get_content_path();
     
     println!("索引路径: {}", index_path.display());
@@ -30,13 +89,21 @@ fn main() -> Result<()> {
         ));
     }
     
-    let engine = SearchEngine::new(index_path.to_str().unwrap())?;
+    // 每次都从头重建，避免重复文档和旧的索引结构
+    let engine = SearchEngine::recreate(index_path.to_str().unwrap(), AnalysisConfig::default())?;
+
+    // 文章热度在索引时写入快速字段
+    let config = ServiceConfig::load(&ServiceConfig::path())?;
+    if config.popularity.path.exists() {
+        let count = engine.load_popularity(&config.popularity.path)?;
+        println!("文章热度: {} 篇（{}）", count, config.popularity.path.display());
+    }
     
     let mut indexed_count = 0;
     // 遍历博客文章
     for entry in WalkDir::new(&content_path) {
         let entry = entry?;
-        if entry.file_type().is_file() && entry.path().extension().map_or(false, |ext| ext == "md") {
+        if entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "md") {
             let content = fs::read_to_string(entry.path())?;
             engine.index_document(&content, entry.path())?;
             println!("已索引: {}", entry.path().display());
@@ -53,6 +120,218 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// 打印文章在查询下的得分解释
+///
+/// 路径先按原样查找，找不到时再按相对于内容目录的路径查找。
+fn explain(query: &str, path: &str) -> Result<()> {
+    let engine = SearchEngine::new(get_index_path().to_str().unwrap())?;
+    let content_relative = get_content_path().join(path).to_string_lossy().to_string();
+
+    let (path, explanation) = match engine.explain(query, path) {
+        Err(SearchError::DocumentNotFound(_)) => {
+            let explanation = engine.explain(query, &content_relative)?;
+            (content_relative.as_str(), explanation)
+        }
+        result => (path, result?),
+    };
+
+    match explanation {
+        Some(explanation) => {
+            println!("文章 {} 匹配查询 \"{}\"，得分 {}", path, query, explanation.value());
+            println!("{}", explanation.to_pretty_json());
+        }
+        None => println!("文章 {} 不匹配查询 \"{}\"", path, query),
+    }
+    Ok(())
+}
+
+/// 为索引中的每篇文章计算相关文章，写入静态 JSON 文件
+fn export_related(output: &Path) -> Result<()> {
+    let engine = SearchEngine::new(get_index_path().to_str().unwrap())?;
+
+    let mut related = BTreeMap::new();
+    for path in engine.document_paths()? {
+        let hits = engine.related(&path, DEFAULT_RELATED_LIMIT)?;
+        related.insert(path, hits);
+    }
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let count = related.len();
+    let json = json!({
+        "version": RELATED_FORMAT_VERSION,
+        "limit": DEFAULT_RELATED_LIMIT,
+        "related": related,
+    });
+    fs::write(output, serde_json::to_string(&json)?)?;
+
+    println!("已写入 {} 篇文章的相关文章: {}", count, output.display());
+    Ok(())
+}
+
+/// 导出静态索引；`--shard` 时按词项首字符拆分倒排表
+fn export_artifact(args: &[String]) -> Result<()> {
+    let mut output = get_static_path().join("search");
+    let mut sharded = false;
+    for arg in args {
+        match arg.as_str() {
+            "--shard" => sharded = true,
+            flag if flag.starts_with("--") => {
+                return Err(anyhow::anyhow!("未知选项: {}\n\n{}", flag, USAGE));
+            }
+            dir => output = PathBuf::from(dir),
+        }
+    }
+
+    let engine = SearchEngine::new(get_index_path().to_str().unwrap())?;
+    let mut artifact = engine.export_artifact()?;
+    let shards = if sharded { artifact.split_shards() } else { BTreeMap::new() };
+
+    // 删除上一次导出的分片，避免清单中不存在的旧分片残留
+    fs::create_dir_all(&output)?;
+    for entry in fs::read_dir(&output)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if name.starts_with("shard-") && name.ends_with(".json") {
+            fs::remove_file(&path)?;
+        }
+    }
+
+    let manifest = serde_json::to_string(&artifact)?;
+    fs::write(output.join(MANIFEST_FILE), &manifest)?;
+    let mut total_bytes = manifest.len();
+    for (key, shard) in &shards {
+        let json = serde_json::to_string(shard)?;
+        total_bytes += json.len();
+        fs::write(output.join(shard_file(key)), json)?;
+    }
+
+    println!("已导出 {} 篇文章到 {}", artifact.docs.len(), output.display());
+    println!("分片: {} 个，共 {} 字节", shards.len(), total_bytes);
+    Ok(())
+}
+
+fn query_report(args: &[String]) -> Result<()> {
+    let mut days = DEFAULT_REPORT_DAYS;
+    let mut top = DEFAULT_REPORT_TOP;
+    let mut json = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--days" => days = option_value(args.next(), "--days")?,
+            "--top" => top = option_value(args.next(), "--top")?,
+            "--json" => json = true,
+            other => return Err(anyhow::anyhow!("未知选项: {}\n\n{}", other, USAGE)),
+        }
+    }
+    if days == 0 {
+        return Err(anyhow::anyhow!("--days 必须大于 0"));
+    }
+
+    let config = ServiceConfig::load(&ServiceConfig::path())?;
+    let dir = &config.query_log.dir;
+    if !config.query_log.enabled {
+        eprintln!("提示: 查询日志未开启，只统计 {} 中已有的日志", dir.display());
+    }
+    let report = QueryReport::from_dir(dir, query_log::now(), days, top)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("最近 {} 天: {} 次搜索，{} 次没有结果", report.days, report.searches, report.zero_result_searches);
+    println!("\n热门查询:");
+    for stat in &report.top_queries {
+        println!("  {:>6}  {:<40} 平均结果 {:.1}，点击 {}", stat.count, stat.query, stat.avg_hits, stat.clicks);
+    }
+    println!("\n无结果查询:");
+    for stat in &report.zero_result_queries {
+        println!("  {:>6}  {}", stat.count, stat.query);
+    }
+    println!("\n趋势词（最近 / 之前 {} 天）:", report.days);
+    for trend in &report.trending_terms {
+        println!("  {:>6} / {:<6} {}", trend.count, trend.previous_count, trend.term);
+    }
+    Ok(())
+}
+
+fn export_popularity() -> Result<()> {
+    let config = ServiceConfig::load(&ServiceConfig::path())?;
+    let popularity_config = &config.popularity;
+    let now = query_log::now();
+    let since = now - i64::from(popularity_config.days) * 86_400;
+    let entries = query_log::read_entries(&config.query_log.dir, since)?;
+    let popularity = Popularity::aggregate(&entries, now, popularity_config.days, popularity_config.half_life_days);
+    popularity.save(&popularity_config.path)?;
+
+    let clicks = entries.iter().filter(|entry| entry.clicked.is_some()).count();
+    println!("最近 {} 天: {} 次点击", popularity_config.days, clicks);
+    println!("文章热度: {} 篇，查询点击信号: {} 个", popularity.posts.len(), popularity.queries.len());
+    println!("已写入 {}，运行 indexer build 使文章热度生效", popularity_config.path.display());
+    Ok(())
+}
+
+/// 监视文章目录，把变更的文章写入索引或从索引中删除，直到进程退出
+///
+/// 启动时先把目录中的文章同步到索引，保留通过管理接口或 webhook 写入的文档。
+/// 同一索引上运行的服务在每次提交后自动重新加载读取器，不需要重启。
+fn watch(args: &[String]) -> Result<()> {
+    let mut debounce = DEFAULT_DEBOUNCE;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--debounce-ms" => debounce = Duration::from_millis(option_value(args.next(), "--debounce-ms")?),
+            other => return Err(anyhow::anyhow!("未知选项: {}\n\n{}", other, USAGE)),
+        }
+    }
+
+    let index_path = get_index_path();
+    let content_path = get_content_path();
+    if !content_path.exists() {
+        return Err(anyhow::anyhow!("博客内容目录不存在: {}", content_path.display()));
+    }
+    fs::create_dir_all(&index_path)?;
+    let engine = Arc::new(SearchEngine::new(index_path.to_str().unwrap())?);
+    let config = ServiceConfig::load(&ServiceConfig::path())?;
+    if config.popularity.path.exists() {
+        engine.load_popularity(&config.popularity.path)?;
+    }
+    let summary = engine.sync_directory(&content_path)?;
+    for skipped in &summary.skipped {
+        eprintln!("已跳过: {}（{}）", skipped.path, skipped.error);
+    }
+    println!("已索引 {} 篇，监视 {}（Ctrl+C 退出）", summary.upserted.len(), content_path.display());
+
+    let watched = Arc::clone(&engine);
+    let _watcher = ContentWatcher::start(&content_path, debounce, move |changes| {
+        match changes.and_then(|paths| watched.sync_documents(&paths)) {
+            Ok(summary) => {
+                for path in &summary.upserted {
+                    println!("已更新: {}", path);
+                }
+                for path in &summary.deleted {
+                    println!("已删除: {}", path);
+                }
+                for skipped in &summary.skipped {
+                    eprintln!("已跳过: {}（{}）", skipped.path, skipped.error);
+                }
+            }
+            Err(e) => eprintln!("更新索引失败: {}", e),
+        }
+    })?;
+    loop {
+        std::thread::park();
+    }
+}
+
+/// 解析选项的值
+fn option_value<T: std::str::FromStr>(value: Option<&String>, name: &str) -> Result<T> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| anyhow::anyhow!("{} 需要一个数字参数\n\n{}", name, USAGE))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,4 +341,4 @@ mod tests {
         let path = get_content_path();
         assert!(path.ends_with("content/blog"));
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,253 @@
+//! 搜索结果缓存
+//!
+//! [`SearchEngine::execute_cached`](crate::SearchEngine::execute_cached) 在执行请求前先查这里。
+//! 缓存按归一化之后的 [`SearchRequest`] 为键，容量满时淘汰最久未使用的条目，
+//! 条目超过 TTL 后失效。读取器加载新的索引代次时清空缓存；每个条目还记录
+//! 计算时的代次，与当前代次不一致时不会返回，避免在重新加载的过程中读到旧结果。
+
+use crate::query::normalize_query;
+use crate::request::{FacetField, Filter, HighlightOptions, HitField, SearchRequest, SearchResponse, SortOrder};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tantivy::{Searcher, SearcherGeneration, Warmer};
+
+/// 一次搜索的缓存状态，通过响应头 `X-Cache` 返回
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// 结果来自缓存
+    Hit,
+    /// 缓存中没有，执行后写入缓存
+    Miss,
+    /// 缓存关闭或请求要求跳过缓存，既不读取也不写入
+    Bypass,
+}
+
+impl CacheStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CacheStatus::Hit => "HIT",
+            CacheStatus::Miss => "MISS",
+            CacheStatus::Bypass => "BYPASS",
+        }
+    }
+}
+
+/// 缓存键：查询经过 NFKC 归一化，过滤条件、分面和返回字段与顺序无关
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    query: String,
+    filters: Vec<Filter>,
+    sort: SortOrder,
+    offset: usize,
+    limit: usize,
+    highlight: Option<HighlightOptions>,
+    facets: Vec<FacetField>,
+    fields: Option<Vec<HitField>>,
+    /// `f32` 没有实现 `Hash`，按位比较
+    min_score: Option<u32>,
+    correct_spelling: bool,
+    explain: bool,
+}
+
+impl CacheKey {
+    pub(crate) fn new(request: &SearchRequest) -> Self {
+        fn sorted<T: Ord + Clone>(items: &[T]) -> Vec<T> {
+            let mut items = items.to_vec();
+            items.sort();
+            items.dedup();
+            items
+        }
+
+        CacheKey {
+            query: normalize_query(&request.query),
+            filters: sorted(&request.filters),
+            sort: request.sort,
+            offset: request.offset,
+            limit: request.limit,
+            highlight: request.highlight,
+            facets: sorted(&request.facets),
+            fields: request.fields.as_deref().map(sorted),
+            min_score: request.min_score.map(f32::to_bits),
+            correct_spelling: request.correct_spelling,
+            explain: request.explain,
+        }
+    }
+}
+
+struct Entry {
+    response: SearchResponse,
+    generation: u64,
+    inserted: Instant,
+    /// 最近一次使用的序号，也是 `Lru::order` 中的键
+    tick: u64,
+}
+
+struct Lru {
+    capacity: usize,
+    ttl: Duration,
+    entries: HashMap<CacheKey, Entry>,
+    /// 使用序号到键，第一个是最久未使用的条目
+    order: BTreeMap<u64, CacheKey>,
+    tick: u64,
+}
+
+impl Lru {
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.tick);
+        }
+    }
+}
+
+/// 带容量和 TTL 限制的 LRU 搜索结果缓存
+pub struct QueryCache {
+    state: Mutex<Lru>,
+}
+
+impl QueryCache {
+    /// `capacity` 为 0 时关闭缓存
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        QueryCache {
+            state: Mutex::new(Lru {
+                capacity,
+                ttl,
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                tick: 0,
+            }),
+        }
+    }
+
+    /// 修改容量和 TTL，同时清空缓存
+    pub fn configure(&self, capacity: usize, ttl: Duration) {
+        *self.state.lock().unwrap() = Lru {
+            capacity,
+            ttl,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+        };
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.lock().unwrap().capacity > 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.clear();
+        state.order.clear();
+    }
+
+    /// 查找在 `generation` 代次上计算、尚未过期的结果
+    pub(crate) fn get(&self, key: &CacheKey, generation: u64) -> Option<SearchResponse> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let ttl = state.ttl;
+        let entry = state.entries.get_mut(key)?;
+        if entry.generation != generation || entry.inserted.elapsed() >= ttl {
+            state.remove(key);
+            return None;
+        }
+
+        state.tick += 1;
+        let key = state.order.remove(&entry.tick)?;
+        entry.tick = state.tick;
+        let response = entry.response.clone();
+        state.order.insert(state.tick, key);
+        Some(response)
+    }
+
+    /// 写入结果；缓存已满时淘汰最久未使用的条目
+    pub(crate) fn insert(&self, key: CacheKey, generation: u64, response: SearchResponse) {
+        let mut state = self.state.lock().unwrap();
+        if state.capacity == 0 {
+            return;
+        }
+
+        state.remove(&key);
+        while state.entries.len() >= state.capacity {
+            match state.order.pop_first() {
+                Some((_, oldest)) => {
+                    state.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+
+        state.tick += 1;
+        let tick = state.tick;
+        state.order.insert(tick, key.clone());
+        state.entries.insert(
+            key,
+            Entry {
+                response,
+                generation,
+                inserted: Instant::now(),
+                tick,
+            },
+        );
+    }
+}
+
+/// 读取器加载新的索引代次时清空缓存
+impl Warmer for QueryCache {
+    fn warm(&self, _searcher: &Searcher) -> tantivy::Result<()> {
+        self.clear();
+        Ok(())
+    }
+
+    fn garbage_collect(&self, _live_generations: &[&SearcherGeneration]) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(total: usize) -> SearchResponse {
+        SearchResponse { total, ..SearchResponse::default() }
+    }
+
+    #[test]
+    fn test_query_cache() {
+        let cache = QueryCache::new(2, Duration::from_secs(60));
+        let rust = CacheKey::new(&SearchRequest::builder("rust").tag("a").tag("b").build());
+        let go = CacheKey::new(&SearchRequest::new("go"));
+        let zig = CacheKey::new(&SearchRequest::new("zig"));
+
+        // 查询归一化之后相同、过滤条件顺序不同的请求使用同一个键
+        assert_eq!(rust, CacheKey::new(&SearchRequest::builder("ｒｕｓｔ ").tag("b").tag("a").build()));
+        assert_ne!(rust, CacheKey::new(&SearchRequest::builder("rust").tag("a").build()));
+
+        cache.insert(rust.clone(), 1, response(1));
+        cache.insert(go.clone(), 1, response(2));
+        assert_eq!(cache.get(&rust, 1).map(|r| r.total), Some(1));
+        // 容量已满，淘汰最久未使用的 go
+        cache.insert(zig.clone(), 1, response(3));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&go, 1).is_none());
+        assert!(cache.get(&zig, 1).is_some());
+
+        // 代次不一致的条目不返回
+        assert!(cache.get(&rust, 2).is_none());
+        assert_eq!(cache.len(), 1);
+
+        let expired = QueryCache::new(2, Duration::ZERO);
+        expired.insert(rust.clone(), 1, response(1));
+        assert!(expired.get(&rust, 1).is_none());
+
+        let disabled = QueryCache::new(0, Duration::from_secs(60));
+        disabled.insert(rust.clone(), 1, response(1));
+        assert!(!disabled.is_enabled());
+        assert!(disabled.is_empty());
+    }
+}
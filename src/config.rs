@@ -0,0 +1,529 @@
+//! 服务配置
+//!
+//! 配置文件为 TOML 格式，默认路径为 `config/service.toml`，可以用环境变量
+//! `BLOG_SEARCH_CONFIG` 指定其他路径。文件不存在时使用默认配置，缺少的
+//! 字段取默认值：
+//!
+//! ```toml
+//! [limits]
+//! max_page_size = 50
+//! max_result_window = 1000
+//! max_query_chars = 200
+//! max_query_terms = 20
+//!
+//! [query_log]
+//! enabled = false
+//! dir = "./data/query_log"
+//! retention_days = 30
+//!
+//! [popularity]
+//! path = "./data/popularity.json"
+//! days = 30
+//! half_life_days = 14.0
+//!
+//! [synonyms]
+//! path = "./config/synonyms.toml"
+//!
+//! [cache]
+//! capacity = 1000
+//! ttl_secs = 60
+//!
+//! [http_cache]
+//! max_age_secs = 0
+//! s_maxage_secs = 60
+//!
+//! [rate_limit]
+//! enabled = true
+//! requests_per_second = 10.0
+//! burst = 30
+//! trusted_proxies = ["127.0.0.1", "::1"]
+//!
+//! [execution]
+//! search_timeout_ms = 2000
+//! max_concurrent_searches = 64
+//!
+//! [cors]
+//! allowed_origins = ["https://your-blog-domain.com", "http://localhost:3000"]
+//! allowed_methods = ["GET", "POST"]
+//! allowed_headers = ["Content-Type", "Accept", "X-Cache-Bypass", "X-Request-Id"]
+//! max_age_secs = 3600
+//!
+//! [content]
+//! dir = "./content/blog"
+//!
+//! [admin]
+//! enabled = false
+//! max_body_bytes = 1048576
+//! max_clock_skew_secs = 300
+//! job_history = 100
+//!
+//! [webhook]
+//! enabled = false
+//! checkout_dir = "./content"
+//! content_path = "blog"
+//! branch = "main"
+//! pull = true
+//! ```
+
+use crate::http::OriginPattern;
+use crate::rate_limit::IpNetwork;
+use crate::{SearchError, SearchResult};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 默认的配置文件路径
+pub const DEFAULT_CONFIG_PATH: &str = "./config/service.toml";
+/// 指定配置文件路径的环境变量
+pub const CONFIG_PATH_ENV: &str = "BLOG_SEARCH_CONFIG";
+
+/// 服务配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServiceConfig {
+    /// 请求校验规则
+    pub limits: RequestLimits,
+    /// 查询日志
+    pub query_log: QueryLogConfig,
+    /// 点击热度
+    pub popularity: PopularityConfig,
+    /// 同义词表
+    pub synonyms: SynonymsConfig,
+    /// 搜索结果缓存
+    pub cache: CacheConfig,
+    /// 浏览器和 CDN 的 HTTP 缓存
+    pub http_cache: HttpCacheConfig,
+    /// 按客户端 IP 的请求限流
+    pub rate_limit: RateLimitConfig,
+    /// 搜索超时和并发上限
+    pub execution: ExecutionConfig,
+    /// 跨域资源共享
+    pub cors: CorsConfig,
+    /// 文章目录
+    pub content: ContentConfig,
+    /// 管理接口
+    pub admin: AdminConfig,
+    /// 内容仓库的推送 webhook
+    pub webhook: WebhookConfig,
+}
+
+/// 搜索请求的校验规则
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RequestLimits {
+    /// `size` 的最大值
+    pub max_page_size: usize,
+    /// 可翻到的最深位置：`page * size` 不能超过该值
+    pub max_result_window: usize,
+    /// 查询的最大字符数（归一化之后）
+    pub max_query_chars: usize,
+    /// 查询中最多的词项数（按空白切分，短语算一个）
+    pub max_query_terms: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        RequestLimits {
+            max_page_size: 50,
+            max_result_window: 1000,
+            max_query_chars: 200,
+            max_query_terms: 20,
+        }
+    }
+}
+
+/// 查询日志的配置，见 `query_log` 模块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueryLogConfig {
+    /// 是否记录查询，默认关闭
+    pub enabled: bool,
+    /// 日志目录
+    pub dir: PathBuf,
+    /// 日志文件保留的天数
+    pub retention_days: u32,
+}
+
+impl Default for QueryLogConfig {
+    fn default() -> Self {
+        QueryLogConfig {
+            enabled: false,
+            dir: PathBuf::from("./data/query_log"),
+            retention_days: 30,
+        }
+    }
+}
+
+/// 点击热度的配置，见 `popularity` 模块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PopularityConfig {
+    /// `indexer popularity` 写入、建立索引和服务启动时读取的热度文件
+    pub path: PathBuf,
+    /// 汇总最近多少天的点击
+    pub days: u32,
+    /// 点击权重减半所需的天数
+    pub half_life_days: f64,
+}
+
+impl Default for PopularityConfig {
+    fn default() -> Self {
+        PopularityConfig {
+            path: PathBuf::from("./data/popularity.json"),
+            days: 30,
+            half_life_days: 14.0,
+        }
+    }
+}
+
+/// 同义词表的配置，见 `synonyms` 模块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SynonymsConfig {
+    /// 同义词与缩写规则文件，Axum 服务和 Vercel 函数都从这里加载；文件不存在时不扩展查询
+    pub path: PathBuf,
+}
+
+impl Default for SynonymsConfig {
+    fn default() -> Self {
+        SynonymsConfig {
+            path: PathBuf::from("./config/synonyms.toml"),
+        }
+    }
+}
+
+/// 搜索结果缓存的配置，见 `cache` 模块
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// 最多缓存的搜索结果数，为 0 时关闭缓存
+    pub capacity: usize,
+    /// 缓存条目的有效期（秒）
+    pub ttl_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            capacity: 1000,
+            ttl_secs: 60,
+        }
+    }
+}
+
+/// 查询接口成功响应的 `Cache-Control`
+///
+/// 响应同时带有 `ETag`，过期之后浏览器和 CDN 用 `If-None-Match` 重新验证，
+/// 索引没有变化时返回 304。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpCacheConfig {
+    /// 浏览器缓存的秒数（`max-age`）
+    pub max_age_secs: u64,
+    /// CDN 缓存的秒数（`s-maxage`），例如 Vercel 的边缘网络
+    pub s_maxage_secs: u64,
+}
+
+impl Default for HttpCacheConfig {
+    fn default() -> Self {
+        HttpCacheConfig {
+            max_age_secs: 0,
+            s_maxage_secs: 60,
+        }
+    }
+}
+
+impl HttpCacheConfig {
+    /// `Cache-Control` 响应头的值
+    pub fn cache_control(&self) -> String {
+        format!("public, max-age={}, s-maxage={}", self.max_age_secs, self.s_maxage_secs)
+    }
+}
+
+/// 按客户端 IP 的令牌桶限流，见 `rate_limit` 模块；只由 Axum 服务的中间件执行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// 是否限流
+    pub enabled: bool,
+    /// 每个客户端每秒补充的请求数
+    pub requests_per_second: f64,
+    /// 每个客户端最多连续发出的请求数
+    pub burst: u32,
+    /// 可信的反向代理地址或地址段，只有来自这些地址的 `X-Forwarded-For` 才会被采用
+    pub trusted_proxies: Vec<String>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            requests_per_second: 10.0,
+            burst: 30,
+            trusted_proxies: vec!["127.0.0.1".to_string(), "::1".to_string()],
+        }
+    }
+}
+
+/// 搜索的执行限制
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExecutionConfig {
+    /// 单次搜索收集结果的时间上限（毫秒），超时返回 503
+    pub search_timeout_ms: u64,
+    /// 同时执行的搜索、建议和相关文章请求数上限，超过时返回 503；只在同一个 [`SearchService`](crate::http::SearchService) 内计数
+    pub max_concurrent_searches: usize,
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        ExecutionConfig {
+            search_timeout_ms: 2000,
+            max_concurrent_searches: 64,
+        }
+    }
+}
+
+/// 跨域资源共享的配置，见 `http::CorsPolicy`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// 允许的来源：`*`、完整的来源或 `https://*.example.com` 形式的子域名
+    pub allowed_origins: Vec<String>,
+    /// 预检请求允许的方法
+    pub allowed_methods: Vec<String>,
+    /// 预检请求允许的请求头
+    pub allowed_headers: Vec<String>,
+    /// 浏览器缓存预检结果的秒数
+    pub max_age_secs: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig {
+            allowed_origins: vec!["https://your-blog-domain.com".to_string(), "http://localhost:3000".to_string()],
+            allowed_methods: vec!["GET".to_string(), "POST".to_string()],
+            allowed_headers: ["Content-Type", "Accept", "X-Cache-Bypass", "X-Request-Id"]
+                .iter()
+                .map(|header| header.to_string())
+                .collect(),
+            max_age_secs: 3600,
+        }
+    }
+}
+
+/// 文章目录的配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContentConfig {
+    /// 服务启动和管理接口重建索引时读取的文章目录，包括子目录
+    pub dir: PathBuf,
+}
+
+impl Default for ContentConfig {
+    fn default() -> Self {
+        ContentConfig {
+            dir: PathBuf::from("./content/blog"),
+        }
+    }
+}
+
+/// Axum 服务的 `/admin` 管理接口，见 `http::AdminService`
+///
+/// 凭据不写在配置文件中，从环境变量读取，见 `http::AdminCredentials::from_env`。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdminConfig {
+    /// 是否开启管理接口，默认关闭
+    pub enabled: bool,
+    /// 上传文档正文的最大字节数，超过时返回 413
+    pub max_body_bytes: usize,
+    /// HMAC 签名请求的时间戳与服务器时间允许相差的秒数
+    pub max_clock_skew_secs: u64,
+    /// 保留的任务记录数，同时也是排队任务数的上限；管理接口和 webhook 共用任务队列
+    pub job_history: usize,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        AdminConfig {
+            enabled: false,
+            max_body_bytes: 1024 * 1024,
+            max_clock_skew_secs: 300,
+            job_history: 100,
+        }
+    }
+}
+
+/// Axum 服务的 `/webhook` 推送接收端，见 `http::WebhookReceiver`
+///
+/// 签名密钥不写在配置文件中，从环境变量读取，见 `http::WEBHOOK_SECRET_ENV`。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// 是否开启 webhook，默认关闭
+    pub enabled: bool,
+    /// 内容仓库在本地的 git checkout
+    pub checkout_dir: PathBuf,
+    /// 文章在仓库中的目录，只处理该目录下的 Markdown 文件
+    pub content_path: String,
+    /// 只处理推送到该分支的事件
+    pub branch: String,
+    /// 同步前是否在 checkout 中执行 `git pull --ff-only`；checkout 由其他方式更新时关闭
+    pub pull: bool,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig {
+            enabled: false,
+            checkout_dir: PathBuf::from("./content"),
+            content_path: "blog".to_string(),
+            branch: "main".to_string(),
+            pull: true,
+        }
+    }
+}
+
+impl ServiceConfig {
+    /// 从 TOML 文本解析并检查取值
+    pub fn from_toml(text: &str) -> SearchResult<Self> {
+        let config: ServiceConfig = toml::from_str(text)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从文件加载；文件不存在时返回默认配置
+    pub fn load(path: &Path) -> SearchResult<Self> {
+        if !path.exists() {
+            return Ok(ServiceConfig::default());
+        }
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// 配置文件路径：环境变量 [`CONFIG_PATH_ENV`]，否则为 [`DEFAULT_CONFIG_PATH`]
+    pub fn path() -> PathBuf {
+        std::env::var_os(CONFIG_PATH_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH))
+    }
+
+    fn validate(&self) -> SearchResult<()> {
+        let limits = &self.limits;
+        let positive = [
+            ("limits.max_page_size", limits.max_page_size),
+            ("limits.max_result_window", limits.max_result_window),
+            ("limits.max_query_chars", limits.max_query_chars),
+            ("limits.max_query_terms", limits.max_query_terms),
+        ];
+        if let Some((name, _)) = positive.iter().find(|(_, value)| *value == 0) {
+            return Err(SearchError::InvalidParameter(format!("{}=0", name)));
+        }
+        if self.query_log.retention_days == 0 {
+            return Err(SearchError::InvalidParameter("query_log.retention_days=0".to_string()));
+        }
+        if self.popularity.days == 0 {
+            return Err(SearchError::InvalidParameter("popularity.days=0".to_string()));
+        }
+        if self.popularity.half_life_days <= 0.0 {
+            return Err(SearchError::InvalidParameter(format!(
+                "popularity.half_life_days={}",
+                self.popularity.half_life_days
+            )));
+        }
+        if self.cache.ttl_secs == 0 {
+            return Err(SearchError::InvalidParameter("cache.ttl_secs=0".to_string()));
+        }
+        if self.rate_limit.requests_per_second <= 0.0 {
+            return Err(SearchError::InvalidParameter(format!(
+                "rate_limit.requests_per_second={}",
+                self.rate_limit.requests_per_second
+            )));
+        }
+        if self.rate_limit.burst == 0 {
+            return Err(SearchError::InvalidParameter("rate_limit.burst=0".to_string()));
+        }
+        for network in &self.rate_limit.trusted_proxies {
+            network.parse::<IpNetwork>()?;
+        }
+        if self.execution.search_timeout_ms == 0 {
+            return Err(SearchError::InvalidParameter("execution.search_timeout_ms=0".to_string()));
+        }
+        if self.execution.max_concurrent_searches == 0 {
+            return Err(SearchError::InvalidParameter("execution.max_concurrent_searches=0".to_string()));
+        }
+        for origin in &self.cors.allowed_origins {
+            origin.parse::<OriginPattern>()?;
+        }
+        let is_token = |value: &String| {
+            !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        };
+        if let Some(method) = self.cors.allowed_methods.iter().find(|method| !is_token(method)) {
+            return Err(SearchError::InvalidParameter(format!("cors.allowed_methods={}", method)));
+        }
+        if let Some(header) = self.cors.allowed_headers.iter().find(|header| !is_token(header)) {
+            return Err(SearchError::InvalidParameter(format!("cors.allowed_headers={}", header)));
+        }
+        if self.admin.max_body_bytes == 0 {
+            return Err(SearchError::InvalidParameter("admin.max_body_bytes=0".to_string()));
+        }
+        if self.admin.job_history == 0 {
+            return Err(SearchError::InvalidParameter("admin.job_history=0".to_string()));
+        }
+        if self.webhook.branch.is_empty() {
+            return Err(SearchError::InvalidParameter("webhook.branch".to_string()));
+        }
+        if limits.max_result_window < limits.max_page_size {
+            return Err(SearchError::InvalidParameter(format!(
+                "limits.max_result_window={}",
+                limits.max_result_window
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() -> SearchResult<()> {
+        assert_eq!(ServiceConfig::from_toml("")?, ServiceConfig::default());
+        let config = ServiceConfig::from_toml("[limits]\nmax_page_size = 20")?;
+        assert_eq!(config.limits.max_page_size, 20);
+        assert_eq!(config.limits.max_query_terms, RequestLimits::default().max_query_terms);
+
+        assert!(ServiceConfig::from_toml("[limits]\nmax_page_size = 0").is_err());
+        assert!(ServiceConfig::from_toml("[limits]\nmax_result_window = 10").is_err());
+        assert!(ServiceConfig::from_toml("[limit]\nmax_page_size = 20").is_err());
+        let config = ServiceConfig::from_toml("[query_log]\nenabled = true\ndir = \"/tmp/queries\"")?;
+        assert!(config.query_log.enabled);
+        assert_eq!(config.query_log.retention_days, QueryLogConfig::default().retention_days);
+        assert!(ServiceConfig::from_toml("[query_log]\nretention_days = 0").is_err());
+        assert!(ServiceConfig::from_toml("[popularity]\nhalf_life_days = 0.0").is_err());
+        let config = ServiceConfig::from_toml("[synonyms]\npath = \"/etc/blog/synonyms.toml\"")?;
+        assert_eq!(config.synonyms.path, Path::new("/etc/blog/synonyms.toml"));
+        assert_eq!(ServiceConfig::from_toml("[cache]\ncapacity = 0")?.cache.ttl_secs, 60);
+        assert!(ServiceConfig::from_toml("[cache]\nttl_secs = 0").is_err());
+        let config = ServiceConfig::from_toml("[http_cache]\nmax_age_secs = 30")?;
+        assert_eq!(config.http_cache.cache_control(), "public, max-age=30, s-maxage=60");
+        let config = ServiceConfig::from_toml("[rate_limit]\ntrusted_proxies = [\"10.0.0.0/8\"]")?;
+        assert_eq!(config.rate_limit.burst, RateLimitConfig::default().burst);
+        assert!(ServiceConfig::from_toml("[rate_limit]\ntrusted_proxies = [\"10.0.0.0/40\"]").is_err());
+        assert!(ServiceConfig::from_toml("[rate_limit]\nrequests_per_second = 0.0").is_err());
+        assert!(ServiceConfig::from_toml("[execution]\nmax_concurrent_searches = 0").is_err());
+        let config = ServiceConfig::from_toml("[cors]\nallowed_origins = [\"https://*.example.com\"]")?;
+        assert_eq!(config.cors.max_age_secs, CorsConfig::default().max_age_secs);
+        assert!(ServiceConfig::from_toml("[cors]\nallowed_origins = [\"example.com\"]").is_err());
+        assert!(ServiceConfig::from_toml("[cors]\nallowed_methods = [\"GET, POST\"]").is_err());
+        let config = ServiceConfig::from_toml("[admin]\nenabled = true")?;
+        assert_eq!(config.admin.job_history, AdminConfig::default().job_history);
+        assert!(ServiceConfig::from_toml("[admin]\njob_history = 0").is_err());
+        assert!(ServiceConfig::from_toml("[admin]\ntoken = \"secret\"").is_err());
+        let config = ServiceConfig::from_toml("[webhook]\nenabled = true\nbranch = \"master\"")?;
+        assert_eq!(config.webhook.content_path, WebhookConfig::default().content_path);
+        assert!(ServiceConfig::from_toml("[webhook]\nbranch = \"\"").is_err());
+        assert_eq!(ServiceConfig::load(Path::new("no/such/service.toml"))?, ServiceConfig::default());
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+//! 搜索超时
+//!
+//! tantivy 的收集器在遍历完所有匹配文档之前不会返回，匹配大量文档的查询可能很慢。
+//! [`DeadlineQuery`] 包装查询，在创建每个段的评分器前后检查截止时间（前缀、模糊等查询
+//! 在创建评分器时展开词项），遍历时每隔 [`CHECK_INTERVAL`] 篇检查一次，超时后中止整个搜索。
+//!
+//! 收集器仍然执行自己的 `collect_segment`：`TopDocs` 按相关度取前几条时经过
+//! [`Weight::for_each_pruning`]，只在回调中检查截止时间，不影响 block-WAND 剪枝。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tantivy::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::{DocId, DocSet, Score, SegmentReader, TantivyError, Term, TERMINATED};
+
+/// 每遍历这么多篇文档检查一次截止时间
+const CHECK_INTERVAL: u32 = 1024;
+
+/// 一次搜索的截止时间
+#[derive(Debug)]
+pub(crate) struct Deadline {
+    at: Instant,
+    expired: AtomicBool,
+}
+
+impl Deadline {
+    pub(crate) fn new(at: Instant) -> Self {
+        Deadline {
+            at,
+            expired: AtomicBool::new(false),
+        }
+    }
+
+    /// 搜索是否因为超时而中止
+    pub(crate) fn expired(&self) -> bool {
+        self.expired.load(Ordering::Relaxed)
+    }
+
+    fn check(&self) -> tantivy::Result<()> {
+        if !self.expired() && Instant::now() < self.at {
+            return Ok(());
+        }
+        self.expired.store(true, Ordering::Relaxed);
+        Err(TantivyError::InternalError("search deadline exceeded".to_string()))
+    }
+}
+
+/// 超过截止时间后中止搜索的查询
+///
+/// 超时的段可能只收集了部分文档，调用方应在搜索结束后检查 [`Deadline::expired`]。
+#[derive(Debug)]
+pub(crate) struct DeadlineQuery {
+    inner: Box<dyn Query>,
+    deadline: Arc<Deadline>,
+}
+
+impl DeadlineQuery {
+    pub(crate) fn new(inner: Box<dyn Query>, deadline: Arc<Deadline>) -> Self {
+        DeadlineQuery { inner, deadline }
+    }
+}
+
+impl Clone for DeadlineQuery {
+    fn clone(&self) -> Self {
+        DeadlineQuery::new(self.inner.box_clone(), Arc::clone(&self.deadline))
+    }
+}
+
+impl Query for DeadlineQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        self.deadline.check()?;
+        Ok(Box::new(DeadlineWeight {
+            inner: self.inner.weight(enable_scoring)?,
+            deadline: Arc::clone(&self.deadline),
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.inner.query_terms(visitor);
+    }
+}
+
+struct DeadlineWeight {
+    inner: Box<dyn Weight>,
+    deadline: Arc<Deadline>,
+}
+
+impl Weight for DeadlineWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        self.deadline.check()?;
+        let inner = self.inner.scorer(reader, boost)?;
+        self.deadline.check()?;
+        Ok(Box::new(DeadlineScorer {
+            inner,
+            deadline: Arc::clone(&self.deadline),
+            visited: 0,
+            terminated: false,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        self.inner.explain(reader, doc)
+    }
+
+    fn for_each_pruning(
+        &self,
+        threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> tantivy::Result<()> {
+        self.deadline.check()?;
+        let mut visited = 0u32;
+        self.inner.for_each_pruning(threshold, reader, &mut |doc, score| {
+            visited += 1;
+            if visited.is_multiple_of(CHECK_INTERVAL) && self.deadline.check().is_err() {
+                // 阈值设为最大值，剩下的文档和块都被跳过
+                return Score::MAX;
+            }
+            callback(doc, score)
+        })?;
+        self.deadline.check()
+    }
+}
+
+/// 每隔 [`CHECK_INTERVAL`] 篇检查截止时间的评分器，超时后提前结束
+struct DeadlineScorer {
+    inner: Box<dyn Scorer>,
+    deadline: Arc<Deadline>,
+    visited: u32,
+    terminated: bool,
+}
+
+impl DocSet for DeadlineScorer {
+    fn advance(&mut self) -> DocId {
+        self.visited += 1;
+        if self.visited.is_multiple_of(CHECK_INTERVAL) && self.deadline.check().is_err() {
+            self.terminated = true;
+        }
+        if self.terminated {
+            return TERMINATED;
+        }
+        self.inner.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.terminated {
+            return TERMINATED;
+        }
+        self.inner.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        if self.terminated {
+            return TERMINATED;
+        }
+        self.inner.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.inner.size_hint()
+    }
+}
+
+impl Scorer for DeadlineScorer {
+    fn score(&mut self) -> Score {
+        self.inner.score()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+    use tantivy::collector::{Count, TopDocs};
+    use tantivy::query::TermQuery;
+    use tantivy::schema::{IndexRecordOption, Schema, TEXT};
+    use tantivy::{doc, Index};
+
+    /// 统计 `for_each_pruning` 回调次数的查询，用来观察收集器是否经过剪枝
+    #[derive(Debug)]
+    struct CountingQuery {
+        inner: Box<dyn Query>,
+        callbacks: Arc<AtomicUsize>,
+    }
+
+    impl Clone for CountingQuery {
+        fn clone(&self) -> Self {
+            CountingQuery {
+                inner: self.inner.box_clone(),
+                callbacks: Arc::clone(&self.callbacks),
+            }
+        }
+    }
+
+    impl Query for CountingQuery {
+        fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+            Ok(Box::new(CountingWeight {
+                inner: self.inner.weight(enable_scoring)?,
+                callbacks: Arc::clone(&self.callbacks),
+            }))
+        }
+    }
+
+    struct CountingWeight {
+        inner: Box<dyn Weight>,
+        callbacks: Arc<AtomicUsize>,
+    }
+
+    impl Weight for CountingWeight {
+        fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+            self.inner.scorer(reader, boost)
+        }
+
+        fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+            self.inner.explain(reader, doc)
+        }
+
+        fn for_each_pruning(
+            &self,
+            threshold: Score,
+            reader: &SegmentReader,
+            callback: &mut dyn FnMut(DocId, Score) -> Score,
+        ) -> tantivy::Result<()> {
+            self.inner.for_each_pruning(threshold, reader, &mut |doc, score| {
+                self.callbacks.fetch_add(1, Ordering::Relaxed);
+                callback(doc, score)
+            })
+        }
+    }
+
+    #[test]
+    fn test_top_docs_still_prunes() -> tantivy::Result<()> {
+        const DOCS: usize = 20_000;
+        let mut schema = Schema::builder();
+        let body = schema.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema.build());
+        let mut writer = index.writer(15_000_000)?;
+        for i in 0..DOCS {
+            // 词频和文档长度不同，得分各不相同
+            let text = format!("{} {}", "rust ".repeat(i % 7 + 1), "filler ".repeat(i % 13));
+            writer.add_document(doc!(body => text))?;
+        }
+        writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let term = TermQuery::new(Term::from_field_text(body, "rust"), IndexRecordOption::WithFreqs);
+        let callbacks = Arc::new(AtomicUsize::new(0));
+        let counting: Box<dyn Query> = Box::new(CountingQuery {
+            inner: Box::new(term.clone()),
+            callbacks: Arc::clone(&callbacks),
+        });
+        let deadline = Arc::new(Deadline::new(Instant::now() + Duration::from_secs(60)));
+        let query = DeadlineQuery::new(counting, Arc::clone(&deadline));
+
+        let top = TopDocs::with_limit(10);
+        let expected = searcher.search(&term, &top)?;
+        assert_eq!(searcher.search(&query, &top)?, expected);
+        assert!(!deadline.expired());
+        // 收集器经过 `for_each_pruning`，只有超过当前阈值的文档才交给收集器，远少于匹配的文档数
+        let callbacks = callbacks.load(Ordering::Relaxed);
+        assert!(callbacks > 0 && callbacks < DOCS / 10, "{} 次回调", callbacks);
+        assert_eq!(searcher.search(&query, &Count)?, DOCS);
+
+        let expired = Arc::new(Deadline::new(Instant::now()));
+        let query = DeadlineQuery::new(Box::new(term), Arc::clone(&expired));
+        assert!(searcher.search(&query, &top).is_err());
+        assert!(expired.expired());
+        Ok(())
+    }
+}
@@ -0,0 +1,293 @@
+//! 索引管理接口
+//!
+//! 配置 `[admin] enabled = true` 时 Axum 服务提供以下接口：
+//!
+//! | 方法 | 路径 | 说明 |
+//! |------|------|------|
+//! | `PUT` | `/admin/documents?path=...` | 正文为 Markdown 原文，写入或替换该路径的文档 |
+//! | `DELETE` | `/admin/documents?path=...` | 删除该路径的文档 |
+//! | `POST` | `/admin/reindex` | 从 `[content] dir` 重建索引 |
+//! | `POST` | `/admin/clear` | 清空索引 |
+//! | `POST` | `/admin/optimize` | 合并索引段 |
+//! | `GET` | `/admin/stats` | 统计索引 |
+//! | `GET` | `/admin/jobs` | 最近的任务，新的在前 |
+//! | `GET` | `/admin/jobs/{id}` | 任务状态 |
+//!
+//! `GET` 接口只读取，直接返回 200 和结果；修改索引的操作作为任务提交给 [`JobQueue`]：接口立即返回 202、
+//! 任务记录和指向任务的 `Location`，任务在后台线程中按提交顺序逐个执行。
+//!
+//! 认证方式任选其一，凭据从环境变量读取，不写在配置文件中：
+//!
+//! - `Authorization: Bearer <token>`，token 为环境变量 [`ADMIN_TOKEN_ENV`] 的值
+//! - HMAC-SHA256 签名，密钥为环境变量 [`ADMIN_SECRET_ENV`] 的值：请求带有
+//!   `X-Admin-Timestamp`（Unix 时间戳，秒）和 `X-Admin-Signature: sha256=<十六进制>`，
+//!   签名内容为 `时间戳\n方法\n路径[?查询]\n` 加上请求正文，见 [`sign`]。时间戳与服务器时间
+//!   相差超过 `max_clock_skew_secs` 时拒绝，限制签名被重放的时间窗口。
+
+use super::jobs::{Job, JobQueue, Operation};
+use super::{ApiError, ApiResponse, ApiResult, QueryParams, RequestContext};
+use crate::{IndexHealth, IndexStats, SearchError, SearchResult};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bearer token 的环境变量
+pub const ADMIN_TOKEN_ENV: &str = "BLOG_SEARCH_ADMIN_TOKEN";
+/// HMAC 签名密钥的环境变量
+pub const ADMIN_SECRET_ENV: &str = "BLOG_SEARCH_ADMIN_SECRET";
+/// HMAC 签名的时间戳请求头
+pub const TIMESTAMP_HEADER: &str = "X-Admin-Timestamp";
+/// HMAC 签名的请求头，取值形如 `sha256=<十六进制>`
+pub const SIGNATURE_HEADER: &str = "X-Admin-Signature";
+/// 签名取值的前缀
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+pub(super) type HmacSha256 = Hmac<Sha256>;
+
+/// 与框架无关的管理请求
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AdminRequest<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub query: Option<&'a str>,
+    /// `Authorization` 请求头
+    pub authorization: Option<&'a str>,
+    /// [`TIMESTAMP_HEADER`] 请求头
+    pub timestamp: Option<&'a str>,
+    /// [`SIGNATURE_HEADER`] 请求头
+    pub signature: Option<&'a str>,
+    pub body: &'a [u8],
+}
+
+impl AdminRequest<'_> {
+    /// 签名覆盖的请求目标：路径加上查询字符串
+    fn target(&self) -> String {
+        match self.query {
+            Some(query) => format!("{}?{}", self.path, query),
+            None => self.path.to_string(),
+        }
+    }
+}
+
+/// 管理接口的凭据：bearer token 和 HMAC 密钥，至少有一个
+#[derive(Clone, Default)]
+pub struct AdminCredentials {
+    token: Option<String>,
+    secret: Option<Vec<u8>>,
+}
+
+impl AdminCredentials {
+    /// 空字符串视为未设置
+    pub fn new(token: Option<String>, secret: Option<String>) -> Self {
+        AdminCredentials {
+            token: token.filter(|token| !token.is_empty()),
+            secret: secret.filter(|secret| !secret.is_empty()).map(String::into_bytes),
+        }
+    }
+
+    /// 从环境变量 [`ADMIN_TOKEN_ENV`] 和 [`ADMIN_SECRET_ENV`] 读取
+    pub fn from_env() -> Self {
+        AdminCredentials::new(std::env::var(ADMIN_TOKEN_ENV).ok(), std::env::var(ADMIN_SECRET_ENV).ok())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.token.is_none() && self.secret.is_none()
+    }
+
+    /// 检查请求的凭据；`now` 为当前的 Unix 时间戳（秒）
+    fn verify(&self, request: &AdminRequest, now: u64, max_clock_skew_secs: u64) -> ApiResult<()> {
+        if let Some(token) = request.authorization.and_then(|value| value.trim().strip_prefix("Bearer ")) {
+            return match &self.token {
+                Some(expected) if constant_time_eq(token.trim().as_bytes(), expected.as_bytes()) => Ok(()),
+                _ => Err(ApiError::unauthorized("invalid bearer token")),
+            };
+        }
+        let (Some(secret), Some(signature)) = (&self.secret, request.signature) else {
+            return Err(ApiError::unauthorized("missing credentials"));
+        };
+        let timestamp = request
+            .timestamp
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .ok_or_else(|| ApiError::unauthorized(format!("missing or invalid {}", TIMESTAMP_HEADER)))?;
+        if timestamp.abs_diff(now) > max_clock_skew_secs {
+            return Err(ApiError::unauthorized("timestamp outside the allowed window"));
+        }
+        let signature = decode_signature(signature)
+            .ok_or_else(|| ApiError::unauthorized(format!("malformed {}", SIGNATURE_HEADER)))?;
+        request_mac(secret, timestamp, request)
+            .verify_slice(&signature)
+            .map_err(|_| ApiError::unauthorized("signature mismatch"))
+    }
+}
+
+fn request_mac(secret: &[u8], timestamp: u64, request: &AdminRequest) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC 接受任意长度的密钥");
+    mac.update(format!("{}\n{}\n{}\n", timestamp, request.method, request.target()).as_bytes());
+    mac.update(request.body);
+    mac
+}
+
+/// 请求的 HMAC 签名，作为 [`SIGNATURE_HEADER`] 的取值；`timestamp` 同时放在 [`TIMESTAMP_HEADER`] 中
+pub fn sign(secret: &[u8], timestamp: u64, request: &AdminRequest) -> String {
+    let digest = request_mac(secret, timestamp, request).finalize().into_bytes();
+    format!("{}{}", SIGNATURE_PREFIX, hex::encode(digest))
+}
+
+/// 把 `sha256=<十六进制>` 形式的签名解码为摘要
+pub(super) fn decode_signature(value: &str) -> Option<Vec<u8>> {
+    value
+        .trim()
+        .strip_prefix(SIGNATURE_PREFIX)
+        .and_then(|digest| hex::decode(digest).ok())
+}
+
+/// 比较所用时间与内容无关，避免按响应时间逐字节猜出 token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// `GET /admin/jobs` 的响应
+#[derive(Debug, Serialize)]
+pub struct JobsResponseBody {
+    pub jobs: Vec<Job>,
+}
+
+/// `GET /admin/stats` 的响应
+#[derive(Debug, Serialize)]
+pub struct StatsResponseBody {
+    pub stats: IndexStats,
+    /// 最近一次提交的状况
+    pub index: IndexHealth,
+}
+
+/// 管理接口：检查凭据，把操作提交到任务队列
+pub struct AdminService {
+    jobs: Arc<JobQueue>,
+    credentials: AdminCredentials,
+}
+
+impl AdminService {
+    /// 没有任何凭据时返回错误
+    pub fn new(jobs: Arc<JobQueue>, credentials: AdminCredentials) -> SearchResult<Self> {
+        if credentials.is_empty() {
+            return Err(SearchError::InvalidParameter(format!(
+                "{} 和 {} 至少设置一个",
+                ADMIN_TOKEN_ENV, ADMIN_SECRET_ENV
+            )));
+        }
+        Ok(AdminService { jobs, credentials })
+    }
+
+    /// 处理 `/admin` 下的请求
+    pub fn handle(&self, ctx: &RequestContext, request: &AdminRequest) -> ApiResponse {
+        let admin = &self.jobs.service().config().admin;
+        if let Err(err) = self.credentials.verify(request, unix_now(), admin.max_clock_skew_secs) {
+            let mut response = ctx.error(&err);
+            response.headers.push(("WWW-Authenticate", "Bearer realm=\"admin\"".to_string()));
+            return response;
+        }
+
+        let path = request.path.trim_end_matches('/');
+        if request.method == "GET" {
+            if path == "/admin/stats" {
+                return ctx.respond(self.stats());
+            }
+            if path == "/admin/jobs" {
+                return ctx.respond(Ok(JobsResponseBody { jobs: self.jobs.recent() }));
+            }
+            if let Some(id) = path.strip_prefix("/admin/jobs/") {
+                return ctx.respond(self.jobs.job(id).ok_or_else(|| ApiError::not_found(format!("job {}", id))));
+            }
+        }
+        match self.operation(request, path).and_then(|operation| self.jobs.submit(operation)) {
+            Ok(job) => {
+                let location = format!("/admin/jobs/{}", job.id);
+                let mut response = ctx.respond(Ok(job));
+                response.status = 202;
+                response.headers.push(("Location", location));
+                response
+            }
+            Err(err) => ctx.error(&err),
+        }
+    }
+
+    /// 按方法和路径解析要提交的操作
+    fn operation(&self, request: &AdminRequest, path: &str) -> ApiResult<Operation> {
+        let params = QueryParams::from_query(request.query);
+        match (request.method, path) {
+            ("PUT", "/admin/documents") => {
+                let path = params.require("path")?;
+                let max_body_bytes = self.jobs.service().config().admin.max_body_bytes;
+                if request.body.len() > max_body_bytes {
+                    return Err(ApiError::payload_too_large(max_body_bytes));
+                }
+                let content = String::from_utf8(request.body.to_vec())
+                    .map_err(|e| ApiError::invalid_parameter("body").with_details(e.to_string()))?;
+                Ok(Operation::Upsert { path, content })
+            }
+            ("DELETE", "/admin/documents") => Ok(Operation::Delete {
+                path: params.require("path")?,
+            }),
+            ("POST", "/admin/reindex") => Ok(Operation::Reindex),
+            ("POST", "/admin/clear") => Ok(Operation::Clear),
+            ("POST", "/admin/optimize") => Ok(Operation::Optimize),
+            (method, path) => Err(ApiError::not_found(format!("{} {}", method, path))),
+        }
+    }
+
+    /// 统计索引，不经过任务队列
+    fn stats(&self) -> ApiResult<StatsResponseBody> {
+        let engine = self.jobs.service().engine();
+        Ok(StatsResponseBody {
+            stats: engine.stats()?,
+            index: engine.health()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credentials() {
+        let credentials = AdminCredentials::new(Some("token".to_string()), Some("secret".to_string()));
+        let request = AdminRequest {
+            method: "POST",
+            path: "/admin/clear",
+            body: b"",
+            ..AdminRequest::default()
+        };
+        let verify = |request: &AdminRequest| credentials.verify(request, 1_000, 300);
+
+        assert!(verify(&request).is_err());
+        assert!(verify(&AdminRequest { authorization: Some("Bearer token"), ..request }).is_ok());
+        assert!(verify(&AdminRequest { authorization: Some("Bearer tokem"), ..request }).is_err());
+
+        let signature = sign(b"secret", 1_000, &request);
+        let signed = AdminRequest {
+            timestamp: Some("1000"),
+            signature: Some(&signature),
+            ..request
+        };
+        assert!(verify(&signed).is_ok());
+        // 过期的时间戳、被改动的请求和错误的密钥都被拒绝
+        assert!(credentials.verify(&signed, 1_301, 300).is_err());
+        assert!(verify(&AdminRequest { path: "/admin/reindex", ..signed }).is_err());
+        assert!(verify(&AdminRequest { body: b"x", ..signed }).is_err());
+        let forged = sign(b"guess", 1_000, &request);
+        assert!(verify(&AdminRequest { signature: Some(&forged), ..signed }).is_err());
+
+        let token_only = AdminCredentials::new(Some("token".to_string()), Some(String::new()));
+        assert!(token_only.verify(&signed, 1_000, 300).is_err());
+        assert!(AdminCredentials::new(None, Some(String::new())).is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+//! 跨域资源共享（CORS）
+//!
+//! Axum 服务和 Vercel 函数使用同一个 [`CorsPolicy`]，由配置文件的 `[cors]` 决定：
+//! 请求的 `Origin` 匹配 `allowed_origins` 中的某一项时，响应带有
+//! `Access-Control-Allow-Origin`（取值为请求的来源）；预检请求（`OPTIONS`）返回 204，
+//! 允许时带有允许的方法、请求头和缓存时间。不允许的来源得不到任何 CORS 响应头，
+//! 由浏览器拒绝。
+//!
+//! 来源的写法：
+//!
+//! - `*`：任意来源，响应头取值为 `*`
+//! - `https://blog.example.com`：协议、主机和端口完全相同
+//! - `https://*.example.com`：`example.com` 的任意子域名（不包括 `example.com` 本身）
+
+use super::ApiResponse;
+use crate::config::CorsConfig;
+use crate::SearchError;
+use std::str::FromStr;
+
+/// 浏览器脚本可以读取的响应头
+const EXPOSED_HEADERS: &str = "X-Request-Id, X-Cache, ETag, Retry-After";
+
+/// 允许的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OriginPattern {
+    /// `*`
+    Any,
+    /// 完整的来源，已转换为小写
+    Exact(String),
+    /// `scheme://*.suffix[:port]`，`suffix` 前带有 `.`
+    Subdomain { scheme: String, suffix: String, port: Option<String> },
+}
+
+impl OriginPattern {
+    pub fn matches(&self, origin: &str) -> bool {
+        let origin = origin.to_ascii_lowercase();
+        match self {
+            OriginPattern::Any => true,
+            OriginPattern::Exact(expected) => origin == *expected,
+            OriginPattern::Subdomain { scheme, suffix, port } => {
+                let Some((origin_scheme, authority)) = origin.split_once("://") else {
+                    return false;
+                };
+                let (host, origin_port) = split_port(authority);
+                origin_scheme == scheme
+                    && origin_port == port.as_deref()
+                    && host.len() > suffix.len()
+                    && host.ends_with(suffix.as_str())
+                    && !host[..host.len() - suffix.len()].contains(['/', '@'])
+            }
+        }
+    }
+}
+
+/// 把 `host:port` 拆开；IPv6 地址带有方括号
+fn split_port(authority: &str) -> (&str, Option<&str>) {
+    match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => (host, Some(port)),
+        _ => (authority, None),
+    }
+}
+
+impl FromStr for OriginPattern {
+    type Err = SearchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SearchError::InvalidParameter(format!("cors.allowed_origins={}", s));
+        let pattern = s.trim().to_ascii_lowercase();
+        if pattern == "*" {
+            return Ok(OriginPattern::Any);
+        }
+        let (scheme, authority) = pattern.split_once("://").ok_or_else(invalid)?;
+        let valid_host = |host: &str| {
+            !host.is_empty()
+                && host
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '[' | ']' | ':'))
+        };
+        if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) {
+            return Err(invalid());
+        }
+        let (host, port) = split_port(authority);
+        if port.is_some_and(|port| port.is_empty() || !port.chars().all(|c| c.is_ascii_digit())) {
+            return Err(invalid());
+        }
+        match host.strip_prefix('*') {
+            Some(suffix) if suffix.starts_with('.') && valid_host(&suffix[1..]) && !suffix.contains('*') => {
+                Ok(OriginPattern::Subdomain {
+                    scheme: scheme.to_string(),
+                    suffix: suffix.to_string(),
+                    port: port.map(str::to_string),
+                })
+            }
+            None if valid_host(host) => Ok(OriginPattern::Exact(pattern.clone())),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// CORS 策略
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorsPolicy {
+    origins: Vec<OriginPattern>,
+    methods: String,
+    headers: String,
+    max_age_secs: u64,
+}
+
+impl CorsPolicy {
+    /// 按配置创建；无法解析的来源被忽略（加载配置时已经校验）
+    pub fn new(config: &CorsConfig) -> Self {
+        CorsPolicy {
+            origins: config.allowed_origins.iter().filter_map(|origin| origin.parse().ok()).collect(),
+            methods: config.allowed_methods.join(", "),
+            headers: config.allowed_headers.join(", "),
+            max_age_secs: config.max_age_secs,
+        }
+    }
+
+    /// 从 [`ServiceConfig::path`](crate::ServiceConfig::path) 加载；配置无效时使用默认策略
+    ///
+    /// Vercel 函数在打开索引之前用它处理预检请求。
+    pub fn load() -> Self {
+        let config = crate::ServiceConfig::load(&crate::ServiceConfig::path()).unwrap_or_default();
+        CorsPolicy::new(&config.cors)
+    }
+
+    fn allows_any(&self) -> bool {
+        self.origins.contains(&OriginPattern::Any)
+    }
+
+    /// 来源是否允许
+    pub fn allows(&self, origin: &str) -> bool {
+        self.origins.iter().any(|pattern| pattern.matches(origin))
+    }
+
+    /// 实际请求的 CORS 响应头
+    pub fn headers(&self, origin: Option<&str>) -> Vec<(&'static str, String)> {
+        let mut headers = Vec::new();
+        if self.allows_any() {
+            headers.push(("Access-Control-Allow-Origin", "*".to_string()));
+        } else {
+            // 响应随请求的来源变化，缓存需要区分
+            headers.push(("Vary", "Origin".to_string()));
+            match origin {
+                Some(origin) if self.allows(origin) => {
+                    headers.push(("Access-Control-Allow-Origin", origin.to_string()));
+                }
+                _ => return headers,
+            }
+        }
+        headers.push(("Access-Control-Expose-Headers", EXPOSED_HEADERS.to_string()));
+        headers
+    }
+
+    /// 给响应加上 CORS 响应头
+    pub fn apply(&self, origin: Option<&str>, mut response: ApiResponse) -> ApiResponse {
+        response.headers.extend(self.headers(origin));
+        response
+    }
+
+    /// 预检请求的响应：204，来源允许时带有允许的方法、请求头和缓存时间
+    pub fn preflight(&self, origin: Option<&str>) -> ApiResponse {
+        let mut headers = self.headers(origin);
+        if headers.iter().any(|(name, _)| *name == "Access-Control-Allow-Origin") {
+            headers.push(("Access-Control-Allow-Methods", self.methods.clone()));
+            headers.push(("Access-Control-Allow-Headers", self.headers.clone()));
+            headers.push(("Access-Control-Max-Age", self.max_age_secs.to_string()));
+        }
+        ApiResponse {
+            status: 204,
+            headers,
+            body: String::new(),
+            internal: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(origins: &[&str]) -> CorsPolicy {
+        CorsPolicy::new(&CorsConfig {
+            allowed_origins: origins.iter().map(|origin| origin.to_string()).collect(),
+            ..CorsConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_origin_patterns() {
+        let exact: OriginPattern = "https://Blog.Example.com".parse().unwrap();
+        assert!(exact.matches("https://blog.example.com"));
+        assert!(!exact.matches("http://blog.example.com"));
+        assert!(!exact.matches("https://blog.example.com:8443"));
+
+        let subdomain: OriginPattern = "https://*.example.com".parse().unwrap();
+        assert!(subdomain.matches("https://blog.example.com"));
+        assert!(subdomain.matches("https://a.b.example.com"));
+        assert!(!subdomain.matches("https://example.com"));
+        assert!(!subdomain.matches("https://evilexample.com"));
+        assert!(!subdomain.matches("https://blog.example.com.evil.net"));
+        assert!(!subdomain.matches("https://blog.example.com:8443"));
+        assert!("http://*.localhost:3000".parse::<OriginPattern>().unwrap().matches("http://app.localhost:3000"));
+
+        for invalid in ["example.com", "https://", "https://*", "https://a.*.com", "https://x.com:abc", "https://x.com/path"] {
+            assert!(invalid.parse::<OriginPattern>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_cors_policy() {
+        let cors = policy(&["https://*.example.com", "http://localhost:3000"]);
+        let header = |headers: &[(&str, String)], name: &str| {
+            headers.iter().find(|(key, _)| *key == name).map(|(_, value)| value.clone())
+        };
+
+        let allowed = cors.headers(Some("https://blog.example.com"));
+        assert_eq!(header(&allowed, "Access-Control-Allow-Origin").as_deref(), Some("https://blog.example.com"));
+        assert_eq!(header(&allowed, "Vary").as_deref(), Some("Origin"));
+
+        let denied = cors.headers(Some("https://evil.net"));
+        assert_eq!(header(&denied, "Access-Control-Allow-Origin"), None);
+        assert!(cors.headers(None).iter().all(|(name, _)| *name == "Vary"));
+
+        let preflight = cors.preflight(Some("http://localhost:3000"));
+        assert_eq!(preflight.status, 204);
+        assert_eq!(preflight.header("Access-Control-Max-Age"), Some("3600"));
+        assert!(preflight.header("Access-Control-Allow-Methods").is_some_and(|methods| methods.contains("POST")));
+        assert_eq!(cors.preflight(Some("https://evil.net")).header("Access-Control-Allow-Methods"), None);
+
+        let any = policy(&["*"]);
+        assert_eq!(header(&any.headers(Some("https://evil.net")), "Access-Control-Allow-Origin").as_deref(), Some("*"));
+    }
+}
@@ -0,0 +1,324 @@
+//! 接口错误与本地化的错误信息
+//!
+//! 错误响应的结构固定为：
+//!
+//! ```json
+//! {
+//!     "code": "invalid_parameter",
+//!     "message": "无效的参数: page=abc",
+//!     "details": null,
+//!     "request_id": "18c5d1f0a2b-0001"
+//! }
+//! ```
+//!
+//! `code` 是稳定的机器可读错误码，`message` 按请求的 `Accept-Language`
+//! 选择中文或英文，`details` 为帮助客户端修正请求的补充说明（可能为 `null`）。
+//! IO、索引目录等服务端错误的原始描述可能含有文件路径，不返回给客户端，
+//! 由前端连同 `request_id` 写入服务端日志。
+
+use crate::{Language, SearchError};
+use serde::Serialize;
+use std::fmt;
+
+/// 机器可读的错误码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// 缺少必填参数
+    MissingParameter,
+    /// 参数无法解析或取值不受支持
+    InvalidParameter,
+    /// 参数超出允许范围，`details` 给出允许的范围
+    ParameterOutOfRange,
+    /// 查询语法错误，例如字段不存在或括号不匹配
+    QuerySyntax,
+    /// 文档不存在
+    DocumentNotFound,
+    /// 缺少凭据或凭据无效
+    Unauthorized,
+    /// 接口或任务不存在
+    NotFound,
+    /// 请求正文超过上限
+    PayloadTooLarge,
+    /// 客户端请求过于频繁
+    RateLimited,
+    /// 同时执行的搜索过多
+    Overloaded,
+    /// 搜索超过时间上限
+    SearchTimeout,
+    /// 索引不存在、无法打开或需要重建
+    IndexUnavailable,
+    /// 文档缺少必需的字段
+    FieldNotFound,
+    /// 读写索引时的 IO 错误
+    IoError,
+    /// 其他内部错误
+    InternalError,
+}
+
+impl ErrorCode {
+    /// 对应的 HTTP 状态码
+    pub fn status(self) -> u16 {
+        match self {
+            ErrorCode::MissingParameter
+            | ErrorCode::InvalidParameter
+            | ErrorCode::ParameterOutOfRange
+            | ErrorCode::QuerySyntax => 400,
+            ErrorCode::DocumentNotFound | ErrorCode::NotFound => 404,
+            ErrorCode::Unauthorized => 401,
+            ErrorCode::PayloadTooLarge => 413,
+            ErrorCode::RateLimited => 429,
+            ErrorCode::Overloaded | ErrorCode::SearchTimeout | ErrorCode::IndexUnavailable => 503,
+            ErrorCode::FieldNotFound | ErrorCode::IoError | ErrorCode::InternalError => 500,
+        }
+    }
+}
+
+/// 接口错误
+///
+/// `subject` 是错误涉及的对象（参数、文档路径或字段名），会出现在错误信息中；
+/// `details` 是返回给客户端的补充说明；`internal` 是只写入服务端日志的底层错误描述；
+/// `retry_after` 为建议的重试间隔（秒），作为 `Retry-After` 响应头返回。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub subject: Option<String>,
+    pub details: Option<String>,
+    pub internal: Option<String>,
+    pub retry_after: Option<u64>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode) -> Self {
+        ApiError { code, subject: None, details: None, internal: None, retry_after: None }
+    }
+
+    /// 服务端错误 `code`，`internal` 只写入日志
+    pub fn internal(code: ErrorCode, internal: impl Into<String>) -> Self {
+        ApiError {
+            internal: Some(internal.into()),
+            ..ApiError::new(code)
+        }
+    }
+
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    pub fn with_retry_after(mut self, seconds: u64) -> Self {
+        self.retry_after = Some(seconds);
+        self
+    }
+
+    /// 缺少参数 `name`
+    pub fn missing_parameter(name: &str) -> Self {
+        ApiError::new(ErrorCode::MissingParameter).with_subject(name)
+    }
+
+    /// 参数无效，`subject` 形如 `page=abc`
+    pub fn invalid_parameter(subject: impl Into<String>) -> Self {
+        ApiError::new(ErrorCode::InvalidParameter).with_subject(subject)
+    }
+
+    /// 参数超出范围，`subject` 形如 `size=100`，`allowed` 形如 `size <= 50`
+    pub fn out_of_range(subject: impl Into<String>, allowed: impl Into<String>) -> Self {
+        ApiError::new(ErrorCode::ParameterOutOfRange)
+            .with_subject(subject)
+            .with_details(allowed)
+    }
+
+    /// 请求过于频繁，`retry_after` 秒之后可以重试
+    pub fn rate_limited(retry_after: u64) -> Self {
+        ApiError::new(ErrorCode::RateLimited).with_retry_after(retry_after.max(1))
+    }
+
+    /// 缺少凭据或凭据无效，`details` 说明原因
+    pub fn unauthorized(details: impl Into<String>) -> Self {
+        ApiError::new(ErrorCode::Unauthorized).with_details(details)
+    }
+
+    /// 接口或任务不存在，`subject` 形如 `GET /admin/unknown`
+    pub fn not_found(subject: impl Into<String>) -> Self {
+        ApiError::new(ErrorCode::NotFound).with_subject(subject)
+    }
+
+    /// 请求正文超过 `max_bytes` 字节
+    pub fn payload_too_large(max_bytes: usize) -> Self {
+        ApiError::new(ErrorCode::PayloadTooLarge).with_subject(max_bytes.to_string())
+    }
+
+    /// 同时执行的搜索过多
+    pub fn overloaded() -> Self {
+        ApiError::new(ErrorCode::Overloaded).with_retry_after(1)
+    }
+
+    /// 索引不可用
+    pub fn index_unavailable(details: impl Into<String>) -> Self {
+        ApiError::new(ErrorCode::IndexUnavailable).with_details(details)
+    }
+
+    /// HTTP 状态码
+    pub fn status(&self) -> u16 {
+        self.code.status()
+    }
+
+    /// 按语言生成错误信息
+    pub fn message(&self, locale: Language) -> String {
+        let subject = self.subject.as_deref().unwrap_or("");
+        match (self.code, locale) {
+            (ErrorCode::MissingParameter, Language::Zh) => format!("缺少参数: {}", subject),
+            (ErrorCode::MissingParameter, Language::En) => format!("Missing parameter: {}", subject),
+            (ErrorCode::InvalidParameter, Language::Zh) => format!("无效的参数: {}", subject),
+            (ErrorCode::InvalidParameter, Language::En) => format!("Invalid parameter: {}", subject),
+            (ErrorCode::ParameterOutOfRange, Language::Zh) => format!("参数超出范围: {}", subject),
+            (ErrorCode::ParameterOutOfRange, Language::En) => format!("Parameter out of range: {}", subject),
+            (ErrorCode::QuerySyntax, Language::Zh) => "查询语法错误".to_string(),
+            (ErrorCode::QuerySyntax, Language::En) => "Invalid query syntax".to_string(),
+            (ErrorCode::DocumentNotFound, Language::Zh) => format!("文档不存在: {}", subject),
+            (ErrorCode::DocumentNotFound, Language::En) => format!("Document not found: {}", subject),
+            (ErrorCode::Unauthorized, Language::Zh) => "缺少凭据或凭据无效".to_string(),
+            (ErrorCode::Unauthorized, Language::En) => "Missing or invalid credentials".to_string(),
+            (ErrorCode::NotFound, Language::Zh) => format!("资源不存在: {}", subject),
+            (ErrorCode::NotFound, Language::En) => format!("Not found: {}", subject),
+            (ErrorCode::PayloadTooLarge, Language::Zh) => format!("请求正文超过 {} 字节", subject),
+            (ErrorCode::PayloadTooLarge, Language::En) => format!("Request body exceeds {} bytes", subject),
+            (ErrorCode::RateLimited, Language::Zh) => "请求过于频繁，请稍后重试".to_string(),
+            (ErrorCode::RateLimited, Language::En) => "Too many requests; retry later".to_string(),
+            (ErrorCode::Overloaded, Language::Zh) => "服务繁忙，请稍后重试".to_string(),
+            (ErrorCode::Overloaded, Language::En) => "The service is busy; retry later".to_string(),
+            (ErrorCode::SearchTimeout, Language::Zh) => "搜索超时，请简化查询后重试".to_string(),
+            (ErrorCode::SearchTimeout, Language::En) => {
+                "The search timed out; simplify the query and retry".to_string()
+            }
+            (ErrorCode::IndexUnavailable, Language::Zh) => "索引不可用，请稍后重试或重新建立索引".to_string(),
+            (ErrorCode::IndexUnavailable, Language::En) => {
+                "The search index is unavailable; retry later or rebuild the index".to_string()
+            }
+            (ErrorCode::FieldNotFound, Language::Zh) => format!("字段不存在: {}", subject),
+            (ErrorCode::FieldNotFound, Language::En) => format!("Field not found: {}", subject),
+            (ErrorCode::IoError, Language::Zh) => "读写索引时发生IO错误".to_string(),
+            (ErrorCode::IoError, Language::En) => "I/O error while accessing the index".to_string(),
+            (ErrorCode::InternalError, Language::Zh) => "内部错误".to_string(),
+            (ErrorCode::InternalError, Language::En) => "Internal error".to_string(),
+        }
+    }
+
+    /// 错误响应的正文
+    pub fn body(&self, locale: Language, request_id: &str) -> ErrorBody {
+        ErrorBody {
+            code: self.code,
+            message: self.message(locale),
+            details: self.details.clone(),
+            request_id: request_id.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message(Language::Zh))?;
+        if let Some(details) = self.details.as_ref().or(self.internal.as_ref()) {
+            write!(f, " ({})", details)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// 只有查询语法错误带有 `details`：其他底层错误的描述可能含有文件路径或索引内部信息，放在 `internal` 中
+impl From<SearchError> for ApiError {
+    fn from(err: SearchError) -> Self {
+        let details = err.to_string();
+        match err {
+            SearchError::InvalidParameter(subject) => ApiError::invalid_parameter(subject),
+            #[cfg(feature = "server")]
+            SearchError::QueryError(_) => ApiError::new(ErrorCode::QuerySyntax).with_details(details),
+            SearchError::DocumentNotFound(path) => {
+                ApiError::new(ErrorCode::DocumentNotFound).with_subject(path)
+            }
+            #[cfg(feature = "server")]
+            SearchError::DirectoryError(_) => ApiError::internal(ErrorCode::IndexUnavailable, details),
+            SearchError::SchemaMismatch => ApiError::internal(ErrorCode::IndexUnavailable, details),
+            SearchError::Timeout => ApiError::new(ErrorCode::SearchTimeout).with_retry_after(1),
+            SearchError::FieldNotFound(field) => {
+                ApiError::new(ErrorCode::FieldNotFound).with_subject(field)
+            }
+            SearchError::IoError(_) => ApiError::internal(ErrorCode::IoError, details),
+            _ => ApiError::internal(ErrorCode::InternalError, details),
+        }
+    }
+}
+
+/// 错误响应的正文
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorBody {
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Option<String>,
+    pub request_id: String,
+}
+
+/// 从 `Accept-Language` 中选择支持的语言，按 `q` 值优先；没有可用语言时为中文
+pub fn negotiate_locale(accept_language: Option<&str>) -> Language {
+    let mut candidates: Vec<(f32, usize, Language)> = accept_language
+        .unwrap_or("")
+        .split(',')
+        .enumerate()
+        .filter_map(|(i, item)| {
+            let mut parts = item.split(';');
+            let language = Language::from_code(parts.next()?)?;
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some((quality, i, language))
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    candidates.first().map(|&(_, _, language)| language).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_locale() {
+        assert_eq!(negotiate_locale(None), Language::Zh);
+        assert_eq!(negotiate_locale(Some("en-US,en;q=0.9")), Language::En);
+        assert_eq!(negotiate_locale(Some("fr, zh;q=0.5, en;q=0.8")), Language::En);
+        assert_eq!(negotiate_locale(Some("en;q=0, de")), Language::Zh);
+    }
+
+    #[test]
+    fn test_error_mapping_and_body() {
+        let error = ApiError::from(SearchError::DocumentNotFound("post.md".to_string()));
+        assert_eq!(error.status(), 404);
+        let body = serde_json::to_value(error.body(Language::En, "req-1")).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "code": "document_not_found",
+                "message": "Document not found: post.md",
+                "details": null,
+                "request_id": "req-1",
+            })
+        );
+
+        assert_eq!(ApiError::from(SearchError::SchemaMismatch).status(), 503);
+        let error = ApiError::from(SearchError::InvalidParameter("sort=random".to_string()));
+        assert_eq!((error.code, error.message(Language::Zh).as_str()), (ErrorCode::InvalidParameter, "无效的参数: sort=random"));
+        let io = std::io::Error::other("/srv/data/search_index/meta.json: disk full");
+        let error = ApiError::from(SearchError::IoError(io));
+        assert_eq!(error.code, ErrorCode::IoError);
+        assert_eq!(error.internal.as_deref(), Some("IO错误: /srv/data/search_index/meta.json: disk full"));
+        assert_eq!(error.body(Language::En, "req-2").details, None);
+    }
+}
@@ -0,0 +1,285 @@
+//! 后台索引任务
+//!
+//! 管理接口、推送 webhook 和服务的文件监视（`--watch`）提交的索引修改都进入同一个 [`JobQueue`]，由一个后台线程
+//! 按提交顺序逐个执行，索引同一时间只有一个写入者。提交后立即得到排队中的 [`Job`]，
+//! 之后按 ID 查询状态：`queued`、`running`，最后为 `succeeded`（带有 `result`）或 `failed`（带有 `error`）。
+
+use super::{generate_request_id, ApiError, ApiResult, ErrorCode, SearchService};
+use crate::{format_datetime, SearchError, SearchResult};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 任务的操作
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// 写入或替换文档，`content` 为包含 front matter 的 Markdown 原文
+    Upsert { path: String, content: String },
+    /// 删除文档
+    Delete { path: String },
+    /// 从 `[content] dir` 重建索引
+    Reindex,
+    /// 清空索引
+    Clear,
+    /// 合并索引段
+    Optimize,
+    /// 按磁盘上的文件同步文档，见 [`SearchEngine::sync_documents`](crate::SearchEngine::sync_documents)；
+    /// `pull` 为 git checkout 的目录时先在其中执行 `git pull --ff-only`
+    Sync { paths: Vec<PathBuf>, pull: Option<PathBuf> },
+}
+
+impl Operation {
+    pub fn kind(&self) -> JobKind {
+        match self {
+            Operation::Upsert { .. } => JobKind::Upsert,
+            Operation::Delete { .. } => JobKind::Delete,
+            Operation::Reindex => JobKind::Reindex,
+            Operation::Clear => JobKind::Clear,
+            Operation::Optimize => JobKind::Optimize,
+            Operation::Sync { .. } => JobKind::Sync,
+        }
+    }
+
+    /// 涉及的文档路径
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Operation::Upsert { path, .. } | Operation::Delete { path } => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// 任务类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Upsert,
+    Delete,
+    Reindex,
+    Clear,
+    Optimize,
+    Sync,
+}
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    /// 任务是否已经结束
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed)
+    }
+}
+
+/// 失败任务的错误
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobError {
+    pub code: ErrorCode,
+    /// 底层错误的原始描述
+    pub message: String,
+}
+
+impl From<SearchError> for JobError {
+    fn from(err: SearchError) -> Self {
+        let message = err.to_string();
+        JobError {
+            code: ApiError::from(err).code,
+            message,
+        }
+    }
+}
+
+/// 任务记录；时间均为 RFC 3339 的 UTC 时间
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Job {
+    pub id: String,
+    pub kind: JobKind,
+    /// 涉及的文档路径，只有写入和删除任务有
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub status: JobStatus,
+    pub created_at: Option<String>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// 成功时的结果
+    pub result: Option<serde_json::Value>,
+    /// 失败时的错误
+    pub error: Option<JobError>,
+}
+
+impl Job {
+    fn new(operation: &Operation) -> Self {
+        Job {
+            id: generate_request_id(),
+            kind: operation.kind(),
+            path: operation.path().map(str::to_string),
+            status: JobStatus::Queued,
+            created_at: now_datetime(),
+            started_at: None,
+            finished_at: None,
+            result: None,
+            error: None,
+        }
+    }
+}
+
+fn now_datetime() -> Option<String> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    format_datetime(secs as i64)
+}
+
+/// 最近的任务记录，按提交顺序排列
+struct JobTable {
+    jobs: VecDeque<Job>,
+    capacity: usize,
+}
+
+impl JobTable {
+    /// 记录新任务；超出容量时丢弃最早结束的任务，未结束的任务已占满容量时返回 `overloaded`
+    fn push(&mut self, job: Job) -> ApiResult<()> {
+        while self.jobs.len() >= self.capacity {
+            match self.jobs.iter().position(|job| job.status.is_finished()) {
+                Some(index) => {
+                    self.jobs.remove(index);
+                }
+                None => return Err(ApiError::overloaded()),
+            }
+        }
+        self.jobs.push_back(job);
+        Ok(())
+    }
+
+    fn get(&self, id: &str) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    fn update(&mut self, id: &str, update: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) {
+            update(job);
+        }
+    }
+}
+
+/// 索引任务队列：记录最近的任务，由一个后台线程逐个执行
+pub struct JobQueue {
+    service: Arc<SearchService>,
+    jobs: Arc<Mutex<JobTable>>,
+    sender: Sender<(String, Operation)>,
+}
+
+impl JobQueue {
+    /// 创建并启动执行任务的后台线程，保留的任务数为 `[admin] job_history`
+    pub fn new(service: Arc<SearchService>) -> SearchResult<Self> {
+        let jobs = Arc::new(Mutex::new(JobTable {
+            jobs: VecDeque::new(),
+            capacity: service.config().admin.job_history,
+        }));
+        let (sender, receiver) = mpsc::channel::<(String, Operation)>();
+        let worker = (Arc::clone(&service), Arc::clone(&jobs));
+        // 队列释放后通道关闭，线程随之退出
+        std::thread::Builder::new()
+            .name("index-jobs".to_string())
+            .spawn(move || {
+                let (service, jobs) = worker;
+                for (id, operation) in receiver {
+                    run_job(&service, &jobs, &id, operation);
+                }
+            })?;
+        Ok(JobQueue { service, jobs, sender })
+    }
+
+    pub fn service(&self) -> &SearchService {
+        &self.service
+    }
+
+    /// 提交任务，立即返回排队中的任务记录
+    pub fn submit(&self, operation: Operation) -> ApiResult<Job> {
+        let job = Job::new(&operation);
+        self.jobs.lock().unwrap().push(job.clone())?;
+        self.sender
+            .send((job.id.clone(), operation))
+            .map_err(|_| ApiError::new(ErrorCode::InternalError).with_details("job worker stopped"))?;
+        Ok(job)
+    }
+
+    /// 任务记录；任务不存在或已被较新的记录替换时返回 `None`
+    pub fn job(&self, id: &str) -> Option<Job> {
+        self.jobs.lock().unwrap().get(id).cloned()
+    }
+
+    /// 最近的任务，新的在前
+    pub fn recent(&self) -> Vec<Job> {
+        self.jobs.lock().unwrap().jobs.iter().rev().cloned().collect()
+    }
+}
+
+fn run_job(service: &SearchService, jobs: &Mutex<JobTable>, id: &str, operation: Operation) {
+    jobs.lock().unwrap().update(id, |job| {
+        job.status = JobStatus::Running;
+        job.started_at = now_datetime();
+    });
+    let outcome = execute(service, operation);
+    jobs.lock().unwrap().update(id, |job| {
+        job.finished_at = now_datetime();
+        match outcome {
+            Ok(result) => {
+                job.status = JobStatus::Succeeded;
+                job.result = Some(result);
+            }
+            Err(err) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(err.into());
+            }
+        }
+    });
+}
+
+fn execute(service: &SearchService, operation: Operation) -> SearchResult<serde_json::Value> {
+    let engine = service.engine();
+    Ok(match operation {
+        Operation::Upsert { path, content } => {
+            json!({ "replaced": engine.upsert_document(&content, Path::new(&path))? })
+        }
+        Operation::Delete { path } => json!({ "deleted": engine.delete_document(&path)? }),
+        Operation::Reindex => json!({ "indexed": engine.reindex(&service.config().content.dir)? }),
+        Operation::Clear => {
+            engine.clear()?;
+            json!({})
+        }
+        Operation::Optimize => json!({ "merged_segments": engine.optimize()? }),
+        Operation::Sync { paths, pull } => {
+            if let Some(dir) = pull {
+                git_pull(&dir)?;
+            }
+            json!(engine.sync_documents(&paths)?)
+        }
+    })
+}
+
+/// 在 git checkout 中执行 `git pull --ff-only`
+fn git_pull(dir: &Path) -> SearchResult<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["pull", "--ff-only", "--quiet"])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(std::io::Error::other(format!("git pull 失败: {}", stderr.trim())).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,905 @@
+//! Axum 服务与 Vercel 函数共用的 HTTP 层
+//!
+//! 查询参数解析、校验、响应结构和错误到状态码的映射都在这里完成，
+//! 两个前端只负责把 [`ApiResponse`] 转换成各自框架的响应，因此返回的
+//! JSON 结构和错误行为保持一致。
+//!
+//! 成功时返回各接口的响应结构；失败时返回 [`ErrorBody`]，状态码为
+//! 400（参数无效或查询语法错误）、401（管理接口的凭据或 webhook 的签名无效）、404（文档或任务不存在）、
+//! 413（管理接口上传的文档过大）、429（请求过于频繁）、
+//! 503（索引不可用、搜索超时或并发已满）或 500（其他错误）。每个响应都带有
+//! `X-Request-Id`，429 和 503 还带有 `Retry-After`。
+//!
+//! 搜索参数在执行前按 [`RequestLimits`] 校验：`page` 从 1 开始，`size`
+//! 不超过配置的上限，查询先做 Unicode NFKC 归一化，再检查字符数和词项数。
+//! 超出范围的参数返回 400 和 `parameter_out_of_range`，`details` 给出允许的范围。
+//!
+//! 搜索结果经过引擎的结果缓存，成功的搜索响应带有 `X-Cache: HIT|MISS|BYPASS`；
+//! 请求带有 `X-Cache-Bypass: 1` 时跳过缓存，便于调试。
+//!
+//! 查询接口的成功响应带有 `ETag`（由索引内容版本和请求计算）和配置的
+//! `Cache-Control`，参数校验通过且请求的 `If-None-Match` 匹配时返回 304 且不执行查询；
+//! 错误响应带有 `Cache-Control: no-store`。
+
+use crate::config::RequestLimits;
+#[cfg(feature = "server")]
+use crate::config::ServiceConfig;
+use crate::query::{normalize_query, split_query};
+use crate::request::{parse_list, DEFAULT_PAGE_SIZE};
+#[cfg(feature = "server")]
+use crate::query_log::QueryLog;
+#[cfg(feature = "server")]
+use crate::{IndexHealth, SearchEngine, SearchError};
+use crate::{
+    FacetField, HitField, Language, SearchHit, SearchRequest, SortOrder, Suggestion,
+    DEFAULT_RELATED_LIMIT, DEFAULT_SUGGEST_LIMIT,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "server")]
+use std::sync::atomic::AtomicUsize;
+#[cfg(feature = "server")]
+use std::time::Duration;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "server")]
+mod admin;
+mod cors;
+mod error;
+#[cfg(feature = "server")]
+mod jobs;
+#[cfg(feature = "server")]
+mod vercel;
+#[cfg(feature = "server")]
+mod webhook;
+
+#[cfg(feature = "server")]
+pub use admin::{
+    sign, AdminCredentials, AdminRequest, AdminService, JobsResponseBody, StatsResponseBody, ADMIN_SECRET_ENV,
+    ADMIN_TOKEN_ENV, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+#[cfg(feature = "server")]
+pub use jobs::{Job, JobError, JobKind, JobQueue, JobStatus, Operation};
+#[cfg(feature = "server")]
+pub use vercel::VercelAdapter;
+#[cfg(feature = "server")]
+pub use webhook::{
+    WebhookReceiver, WebhookRequest, WebhookResponseBody, GITHUB_EVENT_HEADER, HUB_SIGNATURE_HEADER,
+    MAX_WEBHOOK_BODY_BYTES, WEBHOOK_SECRET_ENV,
+};
+pub use cors::{CorsPolicy, OriginPattern};
+pub use error::{negotiate_locale, ApiError, ErrorBody, ErrorCode};
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// 单次建议请求允许的最大数量
+pub const MAX_SUGGEST_LIMIT: usize = 20;
+/// 单次相关文章请求允许的最大数量
+pub const MAX_RELATED_LIMIT: usize = 20;
+/// 所有响应的内容类型
+pub const CONTENT_TYPE: &str = "application/json; charset=utf-8";
+/// 请求 ID 的请求头和响应头
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+/// 要求跳过结果缓存的请求头，取值为 `0` 或 `false` 以外的任意值时生效
+pub const CACHE_BYPASS_HEADER: &str = "X-Cache-Bypass";
+/// 搜索响应中表示缓存状态的响应头
+pub const CACHE_STATUS_HEADER: &str = "X-Cache";
+/// 条件请求的请求头
+pub const IF_NONE_MATCH_HEADER: &str = "If-None-Match";
+/// 实体标签的响应头
+pub const ETAG_HEADER: &str = "ETag";
+pub const CACHE_CONTROL_HEADER: &str = "Cache-Control";
+/// 429 和 503 响应中建议的重试间隔（秒）
+pub const RETRY_AFTER_HEADER: &str = "Retry-After";
+/// 沿用客户端请求 ID 时允许的最大长度
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+/// 与框架无关的响应：状态码、额外的响应头和 JSON 正文
+///
+/// 内容类型固定为 [`CONTENT_TYPE`]，由前端设置。`internal` 为服务端错误的底层描述，
+/// 不返回给客户端，由前端连同正文（含 `request_id`）写入日志。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+    pub internal: Option<String>,
+}
+
+impl ApiResponse {
+    /// 是否为服务端错误，前端据此记录日志
+    pub fn is_server_error(&self) -> bool {
+        self.status >= 500
+    }
+
+    /// 响应头的值
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// 单个请求的上下文：请求 ID、错误信息使用的语言、是否跳过结果缓存以及条件请求的 `If-None-Match`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestContext {
+    pub request_id: String,
+    pub locale: Language,
+    pub cache_bypass: bool,
+    pub if_none_match: Option<String>,
+}
+
+impl RequestContext {
+    /// 从请求头创建；客户端传入合法的 `X-Request-Id` 时沿用，否则生成新的 ID
+    pub fn from_headers(accept_language: Option<&str>, request_id: Option<&str>) -> Self {
+        let request_id = request_id
+            .map(str::trim)
+            .filter(|id| is_valid_request_id(id))
+            .map_or_else(generate_request_id, str::to_string);
+        RequestContext {
+            request_id,
+            locale: negotiate_locale(accept_language),
+            cache_bypass: false,
+            if_none_match: None,
+        }
+    }
+
+    /// 按 [`CACHE_BYPASS_HEADER`] 请求头决定是否跳过结果缓存
+    pub fn with_cache_bypass(mut self, value: Option<&str>) -> Self {
+        self.cache_bypass = value
+            .map(str::trim)
+            .is_some_and(|value| !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("false"));
+        self
+    }
+
+    /// 记录 [`IF_NONE_MATCH_HEADER`] 请求头
+    pub fn with_if_none_match(mut self, value: Option<&str>) -> Self {
+        self.if_none_match = value.map(str::to_string);
+        self
+    }
+
+    /// 把处理结果转换为响应
+    pub fn respond<T: Serialize>(&self, result: ApiResult<T>) -> ApiResponse {
+        let value = result.and_then(|value| {
+            serde_json::to_string(&value).map_err(|e| ApiError::internal(ErrorCode::InternalError, e.to_string()))
+        });
+        match value {
+            Ok(body) => ApiResponse {
+                status: 200,
+                headers: vec![(REQUEST_ID_HEADER, self.request_id.clone())],
+                body,
+                internal: None,
+            },
+            Err(err) => self.error(&err),
+        }
+    }
+
+    /// 错误响应，`Content-Language` 为错误信息的语言
+    pub fn error(&self, err: &ApiError) -> ApiResponse {
+        let body = err.body(self.locale, &self.request_id);
+        let mut headers = vec![
+            (REQUEST_ID_HEADER, self.request_id.clone()),
+            ("Content-Language", self.locale.code().to_string()),
+            (CACHE_CONTROL_HEADER, "no-store".to_string()),
+        ];
+        if let Some(seconds) = err.retry_after {
+            headers.push((RETRY_AFTER_HEADER, seconds.to_string()));
+        }
+        ApiResponse {
+            status: err.status(),
+            headers,
+            body: serde_json::to_string(&body).unwrap_or_default(),
+            internal: err.internal.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+/// 弱实体标签：索引内容版本加上路由和查询字符串的哈希
+///
+/// 响应中的 `took_ms` 每次都不同，因此使用弱标签。标签在 Vercel 实例之间和服务重启前后比较，
+/// 因此使用 SHA-256，不使用随 Rust 版本变化的标准库哈希。
+fn entity_tag(version: &str, route: &str, query: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(route);
+    hasher.update([0]);
+    hasher.update(query.unwrap_or_default());
+    format!("W/\"{}-{}\"", version, crate::short_digest(hasher))
+}
+
+#[cfg(feature = "server")]
+/// `If-None-Match` 是否匹配实体标签，按弱比较忽略 `W/` 前缀
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    if_none_match
+        .split(',')
+        .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// 生成请求 ID：毫秒时间戳加进程内计数器
+fn generate_request_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis());
+    let sequence = COUNTER.fetch_add(1, Ordering::Relaxed) & 0xffff;
+    format!("{:x}-{:04x}", millis, sequence)
+}
+
+/// 解码后的查询参数
+struct QueryParams(HashMap<String, String>);
+
+impl QueryParams {
+    fn from_query(query: Option<&str>) -> Self {
+        QueryParams(
+            form_urlencoded::parse(query.unwrap_or("").as_bytes())
+                .into_owned()
+                .collect(),
+        )
+    }
+
+    /// 非空的字符串参数
+    fn get(&self, name: &str) -> Option<String> {
+        self.0.get(name).filter(|value| !value.is_empty()).cloned()
+    }
+
+    fn require(&self, name: &str) -> ApiResult<String> {
+        self.get(name).ok_or_else(|| ApiError::missing_parameter(name))
+    }
+
+    /// 数值或布尔参数；无法解析时返回 400
+    fn parse<T: FromStr>(&self, name: &str) -> ApiResult<Option<T>> {
+        match self.get(name) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| ApiError::invalid_parameter(format!("{}={}", name, value))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// `/api/search` 的参数
+///
+/// 浏览器端的 wasm 接口直接从 JS 对象反序列化该结构。
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SearchParams {
+    pub q: String,
+    pub page: usize,
+    pub size: usize,
+    pub lang: Option<String>,
+    /// 逗号分隔的标签，结果需包含全部标签
+    pub tags: Option<String>,
+    /// `relevance`、`newest` 或 `oldest`
+    pub sort: Option<String>,
+    pub highlight: bool,
+    /// 逗号分隔的分面字段
+    pub facets: Option<String>,
+    /// 逗号分隔的返回字段
+    pub fields: Option<String>,
+    pub min_score: Option<f32>,
+    /// 为 `true` 时为每条结果附带得分解释
+    pub explain: bool,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        SearchParams {
+            q: String::new(),
+            page: 1,
+            size: DEFAULT_PAGE_SIZE,
+            lang: None,
+            tags: None,
+            sort: None,
+            highlight: false,
+            facets: None,
+            fields: None,
+            min_score: None,
+            explain: false,
+        }
+    }
+}
+
+impl SearchParams {
+    /// 从 URL 查询字符串解析，`q` 为必填参数
+    pub fn from_query(query: Option<&str>) -> ApiResult<Self> {
+        let params = QueryParams::from_query(query);
+        let defaults = SearchParams::default();
+        Ok(SearchParams {
+            q: params.require("q")?,
+            page: params.parse("page")?.unwrap_or(defaults.page),
+            size: params.parse("size")?.unwrap_or(defaults.size),
+            lang: params.get("lang"),
+            tags: params.get("tags"),
+            sort: params.get("sort"),
+            highlight: params.parse("highlight")?.unwrap_or(false),
+            facets: params.get("facets"),
+            fields: params.get("fields"),
+            min_score: params.parse("min_score")?,
+            explain: params.parse("explain")?.unwrap_or(false),
+        })
+    }
+
+    /// 归一化查询并按 `limits` 校验分页和查询长度
+    pub fn validate(&mut self, limits: &RequestLimits) -> ApiResult<()> {
+        self.q = validate_query(&self.q, limits)?;
+        if self.page == 0 {
+            return Err(ApiError::out_of_range("page=0", "page >= 1"));
+        }
+        if self.size == 0 || self.size > limits.max_page_size {
+            return Err(ApiError::out_of_range(
+                format!("size={}", self.size),
+                format!("1 <= size <= {}", limits.max_page_size),
+            ));
+        }
+        if self
+            .page
+            .checked_mul(self.size)
+            .is_none_or(|end| end > limits.max_result_window)
+        {
+            return Err(ApiError::out_of_range(
+                format!("page={}", self.page),
+                format!("page * size <= {}", limits.max_result_window),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 转换为搜索请求
+    pub fn to_request(&self) -> ApiResult<SearchRequest> {
+        let mut builder = SearchRequest::builder(self.q.as_str())
+            .offset(self.page.saturating_sub(1).saturating_mul(self.size))
+            .limit(self.size)
+            .highlight(self.highlight)
+            .explain(self.explain)
+            .correct_spelling(true);
+        if let Some(code) = self.lang.as_deref().filter(|code| !code.is_empty()) {
+            let language = Language::from_code(code)
+                .ok_or_else(|| ApiError::invalid_parameter(format!("lang={}", code)))?;
+            builder = builder.lang(language);
+        }
+        for tag in self.tags.as_deref().unwrap_or("").split(',').map(str::trim) {
+            if !tag.is_empty() {
+                builder = builder.tag(tag);
+            }
+        }
+        if let Some(sort) = self.sort.as_deref().filter(|sort| !sort.is_empty()) {
+            builder = builder.sort(sort.parse::<SortOrder>()?);
+        }
+        if let Some(facets) = self.facets.as_deref() {
+            for facet in parse_list::<FacetField>(facets)? {
+                builder = builder.facet(facet);
+            }
+        }
+        if let Some(fields) = self.fields.as_deref() {
+            builder = builder.fields(parse_list::<HitField>(fields)?);
+        }
+        if let Some(min_score) = self.min_score {
+            builder = builder.min_score(min_score);
+        }
+        Ok(builder.build())
+    }
+}
+
+/// 归一化查询并检查字符数和词项数
+///
+/// 查询字符串中无法按 UTF-8 解码的字节在解析时被替换为 U+FFFD，这里作为无效参数拒绝。
+fn validate_query(q: &str, limits: &RequestLimits) -> ApiResult<String> {
+    if q.contains(char::REPLACEMENT_CHARACTER) {
+        return Err(ApiError::invalid_parameter("q").with_details("invalid UTF-8"));
+    }
+    let q = normalize_query(q);
+    if q.is_empty() {
+        return Err(ApiError::missing_parameter("q"));
+    }
+    let chars = q.chars().count();
+    if chars > limits.max_query_chars {
+        return Err(ApiError::out_of_range(
+            format!("q.chars={}", chars),
+            format!("q.chars <= {}", limits.max_query_chars),
+        ));
+    }
+    let terms = split_query(&q).len();
+    if terms > limits.max_query_terms {
+        return Err(ApiError::out_of_range(
+            format!("q.terms={}", terms),
+            format!("q.terms <= {}", limits.max_query_terms),
+        ));
+    }
+    Ok(q)
+}
+
+/// `/api/suggest` 的参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestParams {
+    pub q: String,
+    /// 已限制在 [`MAX_SUGGEST_LIMIT`] 以内
+    pub limit: usize,
+}
+
+impl SuggestParams {
+    pub fn from_query(query: Option<&str>) -> ApiResult<Self> {
+        let params = QueryParams::from_query(query);
+        Ok(SuggestParams {
+            q: params.require("q")?,
+            limit: params
+                .parse("limit")?
+                .unwrap_or(DEFAULT_SUGGEST_LIMIT)
+                .min(MAX_SUGGEST_LIMIT),
+        })
+    }
+
+    /// 归一化前缀并按 `limits` 检查长度
+    pub fn validate(&mut self, limits: &RequestLimits) -> ApiResult<()> {
+        self.q = validate_query(&self.q, limits)?;
+        Ok(())
+    }
+}
+
+/// `/api/related` 的参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelatedParams {
+    pub path: String,
+    /// 已限制在 [`MAX_RELATED_LIMIT`] 以内
+    pub limit: usize,
+}
+
+impl RelatedParams {
+    pub fn from_query(query: Option<&str>) -> ApiResult<Self> {
+        let params = QueryParams::from_query(query);
+        Ok(RelatedParams {
+            path: params.require("path")?,
+            limit: params
+                .parse("limit")?
+                .unwrap_or(DEFAULT_RELATED_LIMIT)
+                .min(MAX_RELATED_LIMIT),
+        })
+    }
+}
+
+/// `POST /api/click` 的请求正文
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClickParams {
+    /// 产生该结果的查询
+    pub query: String,
+    /// 被点击的文章路径，与搜索结果中的 `path` 相同
+    pub path: String,
+    /// 文章在结果中的位置，从 1 开始（包括之前各页）
+    pub position: usize,
+}
+
+impl ClickParams {
+    /// 解析 JSON 正文
+    pub fn from_body(body: &[u8]) -> ApiResult<Self> {
+        serde_json::from_slice(body)
+            .map_err(|e| ApiError::invalid_parameter("body").with_details(e.to_string()))
+    }
+
+    /// 归一化查询并检查位置
+    pub fn validate(&mut self, limits: &RequestLimits) -> ApiResult<()> {
+        self.query = validate_query(&self.query, limits)?;
+        if self.path.is_empty() {
+            return Err(ApiError::missing_parameter("path"));
+        }
+        if self.position == 0 || self.position > limits.max_result_window {
+            return Err(ApiError::out_of_range(
+                format!("position={}", self.position),
+                format!("1 <= position <= {}", limits.max_result_window),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// `/api/search` 的响应
+#[derive(Debug, Serialize)]
+pub struct SearchResponseBody {
+    pub query: String,
+    pub page: usize,
+    pub size: usize,
+    #[serde(flatten)]
+    pub outcome: crate::SearchResponse,
+}
+
+/// `/api/suggest` 的响应
+#[derive(Debug, Serialize)]
+pub struct SuggestResponseBody {
+    pub query: String,
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// `/api/related` 的响应
+#[derive(Debug, Serialize)]
+pub struct RelatedResponseBody {
+    pub path: String,
+    pub related: Vec<SearchHit>,
+}
+
+/// `POST /api/click` 的响应
+#[derive(Debug, Serialize)]
+pub struct ClickResponseBody {
+    /// 是否写入了日志；查询日志未开启时为 `false`
+    pub recorded: bool,
+}
+
+/// `/health/live` 和 `/health/ready` 的响应
+#[cfg(feature = "server")]
+#[derive(Debug, Serialize)]
+pub struct HealthResponseBody {
+    /// 固定为 `ok`（存活）或 `ready`（就绪）
+    pub status: &'static str,
+    /// 索引状况，只在就绪检查中返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<IndexHealth>,
+}
+
+/// 处理 `/health/live`：进程能够响应请求即为存活，不检查索引
+#[cfg(feature = "server")]
+pub fn live(ctx: &RequestContext) -> ApiResponse {
+    ctx.respond(Ok(HealthResponseBody {
+        status: "ok",
+        index: None,
+    }))
+}
+
+/// 服务端的请求处理：搜索引擎加上服务配置
+///
+/// Axum 服务在启动时创建一次；Vercel 函数每次调用时用 [`SearchService::open`] 创建。
+#[cfg(feature = "server")]
+pub struct SearchService {
+    engine: SearchEngine,
+    config: ServiceConfig,
+    /// 配置开启查询日志时存在
+    query_log: Option<QueryLog>,
+    /// 正在执行的查询接口请求数，见 [`ExecutionConfig`](crate::ExecutionConfig)
+    in_flight: AtomicUsize,
+}
+
+#[cfg(feature = "server")]
+/// 占用一个并发名额，释放时归还
+pub(crate) struct InFlight<'a>(&'a AtomicUsize);
+
+#[cfg(feature = "server")]
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(feature = "server")]
+impl SearchService {
+    pub fn new(engine: SearchEngine, config: ServiceConfig) -> Self {
+        engine.configure_cache(config.cache.capacity, Duration::from_secs(config.cache.ttl_secs));
+        engine.set_search_timeout(Some(Duration::from_millis(config.execution.search_timeout_ms)));
+        let query_log = QueryLog::from_config(&config.query_log);
+        SearchService {
+            engine,
+            config,
+            query_log,
+            in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    /// 占用一个并发名额；已达到 `max_concurrent_searches` 时返回 `overloaded`
+    pub(crate) fn acquire(&self) -> ApiResult<InFlight<'_>> {
+        let limit = self.config.execution.max_concurrent_searches;
+        self.in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| (n < limit).then_some(n + 1))
+            .map_err(|_| ApiError::overloaded())?;
+        Ok(InFlight(&self.in_flight))
+    }
+
+    /// 打开索引并从 [`ServiceConfig::path`] 加载配置、热度文件和同义词表
+    pub fn open(index_path: &str) -> ApiResult<Self> {
+        let config = ServiceConfig::load(&ServiceConfig::path())
+            .map_err(|e| ApiError::internal(ErrorCode::InternalError, e.to_string()))?;
+        let engine = SearchEngine::new(index_path)
+            .map_err(|e| ApiError::internal(ErrorCode::IndexUnavailable, e.to_string()))?;
+        // 热度只影响 `sort=popular` 的排序，文件缺失或无效时按没有点击处理
+        if config.popularity.path.exists() {
+            let _ = engine.load_popularity(&config.popularity.path);
+        }
+        // 与 Axum 服务使用同一份同义词表，各接口的查询扩展和 ETag 保持一致；
+        // 文件无效时与 Axum 服务一样不扩展查询
+        if config.synonyms.path.exists() {
+            let _ = engine.load_synonyms(&config.synonyms.path);
+        }
+        Ok(SearchService::new(engine, config))
+    }
+
+    pub fn engine(&self) -> &SearchEngine {
+        &self.engine
+    }
+
+    pub fn config(&self) -> &ServiceConfig {
+        &self.config
+    }
+
+    /// 处理 `/health/ready`
+    ///
+    /// 索引能够读取并且至少有一篇文档时返回 200 和索引状况，否则返回 503。
+    pub fn ready(&self, ctx: &RequestContext) -> ApiResponse {
+        let result = self
+            .engine
+            .health()
+            .map_err(|e| ApiError::internal(ErrorCode::IndexUnavailable, e.to_string()))
+            .and_then(|health| {
+                if health.doc_count == 0 {
+                    return Err(ApiError::index_unavailable("index is empty"));
+                }
+                Ok(HealthResponseBody {
+                    status: "ready",
+                    index: Some(health),
+                })
+            });
+        ctx.respond(result)
+    }
+
+    /// 为查询接口加上 `ETag` 和 `Cache-Control`
+    ///
+    /// 参数（`params`）无效时返回 400，不比较 `ETag`。`If-None-Match` 匹配时返回 304，不执行 `handle`，
+    /// 只调用 `revalidated`；否则占用一个并发名额执行 `handle`，名额用完时返回 503。
+    fn conditional<P>(
+        &self,
+        ctx: &RequestContext,
+        route: &str,
+        query: Option<&str>,
+        params: ApiResult<P>,
+        revalidated: impl FnOnce(&P),
+        handle: impl FnOnce(P) -> ApiResponse,
+    ) -> ApiResponse {
+        let params = match params {
+            Ok(params) => params,
+            Err(err) => return ctx.error(&err),
+        };
+        let etag = entity_tag(&self.engine.content_version(), route, query);
+        let cache_control = self.config.http_cache.cache_control();
+        if ctx.if_none_match.as_deref().is_some_and(|value| etag_matches(value, &etag)) {
+            revalidated(&params);
+            return ApiResponse {
+                status: 304,
+                headers: vec![
+                    (REQUEST_ID_HEADER, ctx.request_id.clone()),
+                    (ETAG_HEADER, etag),
+                    (CACHE_CONTROL_HEADER, cache_control),
+                ],
+                body: String::new(),
+                internal: None,
+            };
+        }
+
+        let mut response = match self.acquire() {
+            Ok(_slot) => handle(params),
+            Err(err) => return ctx.error(&err),
+        };
+        if response.status == 200 {
+            response.headers.push((ETAG_HEADER, etag));
+            response.headers.push((CACHE_CONTROL_HEADER, cache_control));
+        }
+        response
+    }
+
+    /// 处理 `/api/search`
+    pub fn search(&self, ctx: &RequestContext, query: Option<&str>) -> ApiResponse {
+        let params = SearchParams::from_query(query).and_then(|mut params| {
+            params.validate(&self.config.limits)?;
+            Ok(params)
+        });
+        self.conditional(
+            ctx,
+            "search",
+            query,
+            params,
+            |params| self.record_revalidated(ctx, params),
+            |params| self.execute_search(ctx, params),
+        )
+    }
+
+    fn execute_search(&self, ctx: &RequestContext, params: SearchParams) -> ApiResponse {
+        let mut cache_status = None;
+        let result = params.to_request().and_then(|request| {
+            let (outcome, status) = self.engine.execute_cached(&request, ctx.cache_bypass)?;
+            cache_status = Some(status);
+            self.record_search(&params, &outcome);
+            Ok(SearchResponseBody {
+                query: params.q,
+                page: params.page,
+                size: params.size,
+                outcome,
+            })
+        });
+        let mut response = ctx.respond(result);
+        if let (Some(status), 200) = (cache_status, response.status) {
+            response.headers.push((CACHE_STATUS_HEADER, status.as_str().to_string()));
+        }
+        response
+    }
+
+    /// 304 的搜索同样写入查询日志：经过结果缓存取得结果数，并发名额用完时不记录
+    fn record_revalidated(&self, ctx: &RequestContext, params: &SearchParams) {
+        if self.query_log.is_none() {
+            return;
+        }
+        let Ok(_slot) = self.acquire() else {
+            return;
+        };
+        if let Ok(request) = params.to_request() {
+            if let Ok((outcome, _)) = self.engine.execute_cached(&request, ctx.cache_bypass) {
+                self.record_search(params, &outcome);
+            }
+        }
+    }
+
+    fn record_search(&self, params: &SearchParams, outcome: &crate::SearchResponse) {
+        if let Some(log) = &self.query_log {
+            if log.record_search(&params.q, outcome.total, outcome.took_ms, params.page).is_err() {
+                self.engine.metrics().query_log_error();
+            }
+        }
+    }
+
+    /// 处理 `POST /api/click`：把点击写入查询日志，供 `indexer popularity` 汇总
+    ///
+    /// 文章不在索引中时返回 404，避免任意路径进入日志。
+    pub fn click(&self, ctx: &RequestContext, body: &[u8]) -> ApiResponse {
+        let result = ClickParams::from_body(body).and_then(|mut params| {
+            params.validate(&self.config.limits)?;
+            if !self.engine.contains(&params.path)? {
+                return Err(SearchError::DocumentNotFound(params.path).into());
+            }
+            let recorded = match &self.query_log {
+                Some(log) => match log.record_click(&params.query, &params.path, params.position) {
+                    Ok(()) => true,
+                    Err(_) => {
+                        self.engine.metrics().query_log_error();
+                        false
+                    }
+                },
+                None => false,
+            };
+            Ok(ClickResponseBody { recorded })
+        });
+        ctx.respond(result)
+    }
+
+    /// 处理 `/api/suggest`
+    pub fn suggest(&self, ctx: &RequestContext, query: Option<&str>) -> ApiResponse {
+        let params = SuggestParams::from_query(query).and_then(|mut params| {
+            params.validate(&self.config.limits)?;
+            Ok(params)
+        });
+        self.conditional(ctx, "suggest", query, params, |_| {}, |params| {
+            let result = self.engine.suggest(&params.q, params.limit).map(|suggestions| SuggestResponseBody {
+                query: params.q,
+                suggestions,
+            });
+            ctx.respond(result.map_err(ApiError::from))
+        })
+    }
+
+    /// 处理 `/api/related`
+    pub fn related(&self, ctx: &RequestContext, query: Option<&str>) -> ApiResponse {
+        let params = RelatedParams::from_query(query);
+        self.conditional(ctx, "related", query, params, |_| {}, |params| {
+            let result = self.engine.related(&params.path, params.limit).map(|related| RelatedResponseBody {
+                path: params.path,
+                related,
+            });
+            ctx.respond(result.map_err(ApiError::from))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_params() {
+        let params = SearchParams::from_query(Some("q=error%20handling&page=2&size=5&highlight=true&tags=rust,%20go"))
+            .unwrap();
+        assert_eq!(params.q, "error handling");
+        let request = params.to_request().unwrap();
+        assert_eq!((request.offset, request.limit), (5, 5));
+        assert!(request.highlight.is_some());
+        assert_eq!(request.filters.len(), 2);
+
+        let params = SearchParams::from_query(Some("q=rust&page=")).unwrap();
+        assert_eq!(params, SearchParams { q: "rust".to_string(), ..SearchParams::default() });
+    }
+
+    #[test]
+    fn test_request_context() {
+        let ctx = RequestContext::from_headers(None, Some("bad id with spaces"));
+        assert_ne!(ctx.request_id, "bad id with spaces");
+        assert_ne!(ctx.request_id, RequestContext::from_headers(None, None).request_id);
+        assert_eq!(ctx.locale, Language::Zh);
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn test_entity_tags() {
+        let etag = entity_tag("0123456789abcdef", "search", Some("q=rust"));
+        // 不同构建必须得到相同的标签
+        assert_eq!(etag, "W/\"0123456789abcdef-0acff36ecffb3641\"");
+        assert_ne!(etag, entity_tag("fedcba9876543210", "search", Some("q=rust")));
+        assert_ne!(etag, entity_tag("0123456789abcdef", "suggest", Some("q=rust")));
+
+        assert!(etag_matches(&etag, &etag));
+        assert!(etag_matches(etag.trim_start_matches("W/"), &etag));
+        assert!(etag_matches(&format!("\"other\", {}", etag), &etag));
+        assert!(etag_matches("*", &etag));
+        assert!(!etag_matches("W/\"other\"", &etag));
+    }
+
+    #[test]
+    fn test_invalid_params_are_bad_requests() {
+        let status = |query: &str| {
+            SearchParams::from_query(Some(query))
+                .and_then(|params| params.to_request())
+                .err()
+                .map(|err| err.code)
+        };
+        assert_eq!(status("page=1"), Some(ErrorCode::MissingParameter));
+        assert_eq!(status("q=rust&size=ten"), Some(ErrorCode::InvalidParameter));
+        assert_eq!(status("q=rust&sort=random"), Some(ErrorCode::InvalidParameter));
+        assert_eq!(status("q=rust&lang=fr"), Some(ErrorCode::InvalidParameter));
+        assert_eq!(status("q=rust&fields=title,body"), Some(ErrorCode::InvalidParameter));
+        assert_eq!(status("q=rust&sort=newest&lang=en-US"), None);
+
+        let ctx = RequestContext::from_headers(Some("en"), Some("abc-123"));
+        let response = ctx.respond(RelatedParams::from_query(Some("limit=3")).map(|p| p.limit));
+        assert_eq!(response.status, 400);
+        assert_eq!(response.header("x-request-id"), Some("abc-123"));
+        assert_eq!(
+            response.body,
+            r#"{"code":"missing_parameter","message":"Missing parameter: path","details":null,"request_id":"abc-123"}"#
+        );
+        assert_eq!(SuggestParams::from_query(Some("q=ru&limit=100")).unwrap().limit, MAX_SUGGEST_LIMIT);
+    }
+
+    #[test]
+    fn test_validate_search_params() {
+        let limits = RequestLimits { max_page_size: 20, max_result_window: 100, max_query_chars: 10, max_query_terms: 3 };
+        let validate = |query: &str| {
+            let mut params = SearchParams::from_query(Some(query))?;
+            params.validate(&limits).map(|_| params)
+        };
+        let error = |query: &str| validate(query).err().map(|err| (err.code, err.subject.unwrap_or_default()));
+
+        let params = validate("q=%EF%BC%B2ust%E3%80%80%20%20%E9%94%99%E8%AF%AF&page=5&size=20").unwrap();
+        assert_eq!(params.q, "Rust 错误");
+        assert_eq!(error("q=rust&page=0"), Some((ErrorCode::ParameterOutOfRange, "page=0".to_string())));
+        assert_eq!(error("q=rust&size=0"), Some((ErrorCode::ParameterOutOfRange, "size=0".to_string())));
+        assert_eq!(error("q=rust&size=21"), Some((ErrorCode::ParameterOutOfRange, "size=21".to_string())));
+        assert_eq!(error("q=rust&page=6&size=20"), Some((ErrorCode::ParameterOutOfRange, "page=6".to_string())));
+        assert_eq!(
+            error(&format!("q=rust&page={}&size=2", usize::MAX)).map(|(code, _)| code),
+            Some(ErrorCode::ParameterOutOfRange)
+        );
+        assert_eq!(error("q=abcdefghijk"), Some((ErrorCode::ParameterOutOfRange, "q.chars=11".to_string())));
+        assert_eq!(error("q=a+b+c+d"), Some((ErrorCode::ParameterOutOfRange, "q.terms=4".to_string())));
+        assert_eq!(validate("q=%22a+b+c+d%22").unwrap().q, "\"a b c d\"");
+        assert_eq!(error("q=%FF%FE"), Some((ErrorCode::InvalidParameter, "q".to_string())));
+        assert_eq!(error("q=%E3%80%80%09"), Some((ErrorCode::MissingParameter, "q".to_string())));
+
+        let error = validate("q=rust&size=100").unwrap_err();
+        assert_eq!(error.status(), 400);
+        assert_eq!(error.details.as_deref(), Some("1 <= size <= 20"));
+        assert_eq!(error.message(Language::En), "Parameter out of range: size=100");
+    }
+}
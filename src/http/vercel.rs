@@ -0,0 +1,138 @@
+//! Vercel 函数的适配层
+//!
+//! `api/` 下的每个函数只负责调用 [`SearchService`](super::SearchService) 的对应接口，
+//! 读取请求头、CORS 和响应转换都在 [`VercelAdapter`] 中完成，与 Axum 服务的行为保持一致。
+
+use super::{
+    live, ApiError, ApiResponse, CorsPolicy, RequestContext, SearchService, CACHE_BYPASS_HEADER, CONTENT_TYPE,
+    IF_NONE_MATCH_HEADER, REQUEST_ID_HEADER,
+};
+use vercel_runtime::{Body, Error, Request, Response, StatusCode};
+
+/// 一次 Vercel 调用的请求和 CORS 策略
+pub struct VercelAdapter<'a> {
+    request: &'a Request,
+    cors: CorsPolicy,
+}
+
+impl<'a> VercelAdapter<'a> {
+    /// 按 [`CorsPolicy::load`] 加载 CORS 策略，不打开索引
+    pub fn new(request: &'a Request) -> Self {
+        VercelAdapter {
+            request,
+            cors: CorsPolicy::load(),
+        }
+    }
+
+    fn header(&self, name: &str) -> Option<&'a str> {
+        self.request.headers().get(name).and_then(|value| value.to_str().ok())
+    }
+
+    /// 从请求头创建共用 HTTP 层的请求上下文
+    pub fn context(&self) -> RequestContext {
+        RequestContext::from_headers(self.header("Accept-Language"), self.header(REQUEST_ID_HEADER))
+            .with_cache_bypass(self.header(CACHE_BYPASS_HEADER))
+            .with_if_none_match(self.header(IF_NONE_MATCH_HEADER))
+    }
+
+    /// 预检请求的响应
+    pub fn preflight(&self) -> Result<Response<Body>, Error> {
+        self.respond(self.cors.preflight(self.header("Origin")))
+    }
+
+    /// `/health/live` 和 `/health/ready` 的响应
+    ///
+    /// `vercel.json` 把两个路径改写为 `/api/health.rs?probe=live|ready`，改写后函数看到的路径
+    /// 不一定是原路径，因此按 `probe` 参数区分：`ready` 打开 `index_path` 的索引并检查文档数，
+    /// `live` 或没有该参数时不打开索引，其他取值返回 400。
+    pub fn health(&self, index_path: &str) -> Result<Response<Body>, Error> {
+        let ctx = self.context();
+        let probe = form_urlencoded::parse(self.request.uri().query().unwrap_or("").as_bytes())
+            .find(|(key, _)| key == "probe")
+            .map(|(_, value)| value.into_owned());
+        let response = match probe.as_deref() {
+            None | Some("live") => live(&ctx),
+            Some("ready") => match SearchService::open(index_path) {
+                Ok(service) => service.ready(&ctx),
+                Err(e) => ctx.error(&e),
+            },
+            Some(_) => ctx.error(&ApiError::invalid_parameter("probe")),
+        };
+        self.respond(response)
+    }
+
+    /// 加上 CORS 响应头并转换为 Vercel 响应；服务端错误写入函数日志
+    ///
+    /// 没有正文的响应（204、304 等）不带 `Content-Type`。
+    pub fn respond(&self, response: ApiResponse) -> Result<Response<Body>, Error> {
+        let response = self.cors.apply(self.header("Origin"), response);
+        if response.is_server_error() {
+            let internal = response.internal.as_deref().unwrap_or("");
+            eprintln!("{} 请求失败: {} {}", self.request.uri().path(), response.body, internal);
+        }
+        let mut builder = Response::builder().status(StatusCode::from_u16(response.status)?);
+        if !response.body.is_empty() {
+            builder = builder.header("Content-Type", CONTENT_TYPE);
+        }
+        for (name, value) in &response.headers {
+            builder = builder.header(*name, value);
+        }
+        Ok(builder.body(Body::Text(response.body))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_respond() -> Result<(), Error> {
+        let request = Request::new(Body::Empty);
+        let vercel = VercelAdapter::new(&request);
+        let ctx = vercel.context();
+
+        let response = vercel.respond(ctx.respond(Ok(serde_json::json!({ "ok": true }))))?;
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["Content-Type"], CONTENT_TYPE);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], ctx.request_id.as_str());
+
+        let not_modified = ApiResponse {
+            status: 304,
+            headers: vec![(REQUEST_ID_HEADER, ctx.request_id.clone())],
+            body: String::new(),
+            internal: None,
+        };
+        let response = vercel.respond(not_modified)?;
+        assert_eq!(response.status(), 304);
+        assert!(!response.headers().contains_key("Content-Type"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_health() -> Result<(), Error> {
+        // 与其他测试一样放在 target/test_indexes 下；就绪检查会在该路径创建空索引
+        let index_dir = std::path::Path::new("target").join("test_indexes").join("vercel_health");
+        let _ = std::fs::remove_dir_all(&index_dir);
+        let missing_index = index_dir.to_str().unwrap();
+        let probe = |uri: &str| -> Result<(u16, serde_json::Value), Error> {
+            let mut request = Request::new(Body::Empty);
+            *request.uri_mut() = uri.parse()?;
+            let response = VercelAdapter::new(&request).health(missing_index)?;
+            let body = match response.body() {
+                Body::Text(text) => serde_json::from_str(text)?,
+                _ => serde_json::Value::Null,
+            };
+            Ok((response.status().as_u16(), body))
+        };
+
+        // 存活检查不打开索引，就绪检查打开索引，索引为空时返回 503
+        let (status, body) = probe("/api/health.rs?probe=live")?;
+        assert_eq!((status, body["status"].as_str()), (200, Some("ok")));
+        let (status, body) = probe("/api/health.rs?probe=ready")?;
+        assert_eq!((status, body["code"].as_str()), (503, Some("index_unavailable")));
+        assert_eq!(probe("/api/health.rs")?.0, 200);
+        assert_eq!(probe("/api/health.rs?probe=deep")?.0, 400);
+        let _ = std::fs::remove_dir_all(&index_dir);
+        Ok(())
+    }
+}
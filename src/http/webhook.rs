@@ -0,0 +1,221 @@
+//! 内容仓库的推送 webhook
+//!
+//! 配置 `[webhook] enabled = true` 时 Axum 服务在 `POST /webhook` 接收 GitHub 格式的
+//! `push` 事件，正文可以是 JSON，也可以是表单格式的 `payload=...`。请求带有
+//! `X-Hub-Signature-256: sha256=<十六进制>`，即以环境变量 [`WEBHOOK_SECRET_ENV`] 为密钥
+//! 对原始正文计算的 HMAC-SHA256，签名不符时返回 401。
+//!
+//! 推送到 `[webhook] branch` 时，从各个提交的 `added`、`modified` 和 `removed` 中取出
+//! `content_path` 目录下的 Markdown 文件，映射到本地 checkout 中的路径，作为一个同步任务
+//! 提交到 [`JobQueue`]：先在 checkout 中 `git pull --ff-only`（`pull = true` 时），
+//! 再按文件在磁盘上的状态写入或删除文档，不需要重建整个索引。接口立即返回 202 和任务记录。
+//!
+//! `ping` 事件返回 200；其他事件、其他分支和分支删除返回 200 和 `ignored`，
+//! 避免 GitHub 把这些投递标记为失败。
+
+use super::admin::{decode_signature, HmacSha256};
+use super::jobs::{Job, JobQueue, Operation};
+use super::{ApiError, ApiResponse, ApiResult, RequestContext};
+use crate::config::WebhookConfig;
+use crate::{SearchError, SearchResult};
+use hmac::Mac;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// 签名密钥的环境变量
+pub const WEBHOOK_SECRET_ENV: &str = "BLOG_SEARCH_WEBHOOK_SECRET";
+/// 正文签名的请求头，取值形如 `sha256=<十六进制>`
+pub const HUB_SIGNATURE_HEADER: &str = "X-Hub-Signature-256";
+/// 事件类型的请求头
+pub const GITHUB_EVENT_HEADER: &str = "X-GitHub-Event";
+/// 请求正文的最大字节数，与 GitHub 投递的上限一致
+pub const MAX_WEBHOOK_BODY_BYTES: usize = 25 * 1024 * 1024;
+
+/// 与框架无关的 webhook 请求
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WebhookRequest<'a> {
+    /// [`GITHUB_EVENT_HEADER`] 请求头
+    pub event: Option<&'a str>,
+    /// [`HUB_SIGNATURE_HEADER`] 请求头
+    pub signature: Option<&'a str>,
+    /// `Content-Type` 请求头
+    pub content_type: Option<&'a str>,
+    pub body: &'a [u8],
+}
+
+/// webhook 的响应
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum WebhookResponseBody {
+    /// 回应 `ping` 事件
+    Pong,
+    /// 已提交同步任务
+    Queued { job: Job },
+    /// 事件无需处理
+    Ignored { reason: String },
+}
+
+/// push 事件中用到的字段
+#[derive(Debug, Deserialize)]
+struct PushEvent {
+    #[serde(rename = "ref")]
+    git_ref: String,
+    #[serde(default)]
+    deleted: bool,
+    #[serde(default)]
+    commits: Vec<PushCommit>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PushCommit {
+    added: Vec<String>,
+    modified: Vec<String>,
+    removed: Vec<String>,
+}
+
+/// 推送 webhook 的接收端：检查签名，把变更的文章作为同步任务提交
+pub struct WebhookReceiver {
+    jobs: Arc<JobQueue>,
+    secret: Vec<u8>,
+    config: WebhookConfig,
+}
+
+impl WebhookReceiver {
+    /// 密钥为空时返回错误
+    pub fn new(jobs: Arc<JobQueue>, secret: Option<String>) -> SearchResult<Self> {
+        let secret = secret
+            .filter(|secret| !secret.is_empty())
+            .ok_or_else(|| SearchError::InvalidParameter(format!("需要设置 {}", WEBHOOK_SECRET_ENV)))?;
+        let config = jobs.service().config().webhook.clone();
+        Ok(WebhookReceiver {
+            jobs,
+            secret: secret.into_bytes(),
+            config,
+        })
+    }
+
+    /// 从环境变量 [`WEBHOOK_SECRET_ENV`] 读取密钥
+    pub fn from_env(jobs: Arc<JobQueue>) -> SearchResult<Self> {
+        WebhookReceiver::new(jobs, std::env::var(WEBHOOK_SECRET_ENV).ok())
+    }
+
+    /// 处理 `POST /webhook`
+    pub fn handle(&self, ctx: &RequestContext, request: &WebhookRequest) -> ApiResponse {
+        if let Err(err) = self.verify(request) {
+            return ctx.error(&err);
+        }
+        let outcome = match request.event.map(str::trim) {
+            Some("ping") => Ok(WebhookResponseBody::Pong),
+            Some("push") => self.push(request),
+            Some(event) => Ok(ignored(format!("event {}", event))),
+            None => Err(ApiError::missing_parameter(GITHUB_EVENT_HEADER)),
+        };
+        match outcome {
+            Ok(body @ WebhookResponseBody::Queued { .. }) => {
+                let mut response = ctx.respond(Ok(body));
+                response.status = 202;
+                response
+            }
+            outcome => ctx.respond(outcome),
+        }
+    }
+
+    /// 检查正文的签名
+    fn verify(&self, request: &WebhookRequest) -> ApiResult<()> {
+        let signature = request
+            .signature
+            .ok_or_else(|| ApiError::unauthorized(format!("missing {}", HUB_SIGNATURE_HEADER)))?;
+        let signature =
+            decode_signature(signature).ok_or_else(|| ApiError::unauthorized(format!("malformed {}", HUB_SIGNATURE_HEADER)))?;
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC 接受任意长度的密钥");
+        mac.update(request.body);
+        mac.verify_slice(&signature)
+            .map_err(|_| ApiError::unauthorized("signature mismatch"))
+    }
+
+    /// 处理 push 事件，提交同步任务
+    fn push(&self, request: &WebhookRequest) -> ApiResult<WebhookResponseBody> {
+        let event: PushEvent = serde_json::from_slice(&payload(request)?)
+            .map_err(|e| ApiError::invalid_parameter("body").with_details(e.to_string()))?;
+        if event.git_ref != format!("refs/heads/{}", self.config.branch) {
+            return Ok(ignored(format!("ref {}", event.git_ref)));
+        }
+        if event.deleted {
+            return Ok(ignored("branch deleted".to_string()));
+        }
+        let paths = self.changed_posts(&event);
+        if paths.is_empty() {
+            return Ok(ignored("no posts changed".to_string()));
+        }
+        let pull = self.config.pull.then(|| self.config.checkout_dir.clone());
+        let job = self.jobs.submit(Operation::Sync { paths, pull })?;
+        Ok(WebhookResponseBody::Queued { job })
+    }
+
+    /// 推送中变更的文章在本地 checkout 中的路径，去重并排序
+    fn changed_posts(&self, event: &PushEvent) -> Vec<PathBuf> {
+        let content_path = Path::new(&self.config.content_path);
+        let files: BTreeSet<&str> = event
+            .commits
+            .iter()
+            .flat_map(|commit| commit.added.iter().chain(&commit.modified).chain(&commit.removed))
+            .map(String::as_str)
+            .collect();
+        files
+            .into_iter()
+            .map(Path::new)
+            .filter(|file| {
+                file.extension().is_some_and(|ext| ext == "md")
+                    && file.starts_with(content_path)
+                    // 仓库中的路径总是相对路径，不允许跳出 checkout
+                    && file.components().all(|component| matches!(component, Component::Normal(_)))
+            })
+            .map(|file| self.config.checkout_dir.join(file))
+            .collect()
+    }
+}
+
+/// 取出 JSON 正文；表单格式的投递中 JSON 在 `payload` 字段里
+fn payload(request: &WebhookRequest) -> ApiResult<Vec<u8>> {
+    let is_form = request
+        .content_type
+        .is_some_and(|value| value.trim().starts_with("application/x-www-form-urlencoded"));
+    if !is_form {
+        return Ok(request.body.to_vec());
+    }
+    form_urlencoded::parse(request.body)
+        .find(|(key, _)| key == "payload")
+        .map(|(_, value)| value.into_owned().into_bytes())
+        .ok_or_else(|| ApiError::missing_parameter("payload"))
+}
+
+fn ignored(reason: String) -> WebhookResponseBody {
+    WebhookResponseBody::Ignored { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload() {
+        let json = br#"{"ref":"refs/heads/main"}"#;
+        let request = WebhookRequest {
+            body: json,
+            ..WebhookRequest::default()
+        };
+        assert_eq!(payload(&request).unwrap(), json);
+
+        let form = b"payload=%7B%22ref%22%3A%22refs%2Fheads%2Fmain%22%7D";
+        let request = WebhookRequest {
+            content_type: Some("application/x-www-form-urlencoded"),
+            body: form,
+            ..WebhookRequest::default()
+        };
+        assert_eq!(payload(&request).unwrap(), json);
+        assert!(payload(&WebhookRequest { body: b"ref=main", ..request }).is_err());
+    }
+}
@@ -1,40 +1,163 @@
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+#[cfg(feature = "server")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "server")]
+use std::sync::{Arc, RwLock, Weak};
+#[cfg(feature = "server")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "server")]
+use std::collections::BTreeMap;
+#[cfg(feature = "server")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "server")]
+use std::time::{Duration, Instant, SystemTime};
+#[cfg(feature = "server")]
 use tantivy::{
-    collector::TopDocs,
+    collector::{Collector, Count, FacetCollector, TopDocs},
     directory::MmapDirectory,
     doc,
-    query::{QueryParser, QueryParserError},
-    schema::{Schema, STORED, TEXT},
+    query::{
+        BooleanQuery, BoostQuery, Explanation, MoreLikeThisQuery, Occur, Query, QueryParser,
+        QueryParserError, TermQuery,
+    },
+    schema::{
+        Facet, FacetOptions, Field, IndexRecordOption, Schema, Term, TextFieldIndexing,
+        TextOptions, FAST, INDEXED, STORED, STRING,
+    },
+    DocAddress,
+    DocId,
+    Document,
     Index,
+    IndexReader,
+    Score,
+    Searcher,
+    SegmentReader,
+    SnippetGenerator,
     TantivyError,
+    Warmer,
     directory::error::OpenDirectoryError,
 };
 use thiserror::Error;
 
+pub mod analysis;
+pub mod artifact;
+#[cfg(feature = "server")]
+pub mod cache;
+pub mod config;
+#[cfg(feature = "server")]
+mod deadline;
+pub mod http;
+#[cfg(feature = "server")]
+pub mod metrics;
+#[cfg(feature = "server")]
+pub mod popularity;
+mod query;
+#[cfg(feature = "server")]
+pub mod query_log;
+pub mod rate_limit;
+pub mod request;
+pub mod spelling;
+pub mod static_search;
+pub mod suggest;
+pub mod synonyms;
+#[cfg(feature = "server")]
+mod tokenizer;
+#[cfg(feature = "server")]
+pub mod watch;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use analysis::{AnalysisConfig, EnglishAnalyzerConfig, Language};
+pub use config::{
+    AdminConfig, CacheConfig, ContentConfig, CorsConfig, ExecutionConfig, HttpCacheConfig,
+    QueryLogConfig, RateLimitConfig, RequestLimits, ServiceConfig, SynonymsConfig, WebhookConfig,
+};
+pub use request::{
+    FacetCount, FacetField, Filter, HighlightOptions, HitField, SearchHit, SearchRequest,
+    SearchRequestBuilder, SearchResponse, SortOrder,
+};
+pub use spelling::SpellingSuggestion;
+pub use static_search::StaticSearch;
+pub use suggest::{Suggestion, SuggestionKind, DEFAULT_SUGGEST_LIMIT};
+#[cfg(feature = "server")]
+use {
+    analysis::{detect_language, Analyzer},
+    artifact::{ArtifactBuilder, ArtifactDoc, SearchArtifact},
+    cache::{CacheKey, CacheStatus, QueryCache},
+    deadline::{Deadline, DeadlineQuery},
+    metrics::Metrics,
+    popularity::Popularity,
+    query::{proximity_phrase, PROXIMITY_BOOST},
+    spelling::SpellDictionary,
+    suggest::SuggestIndex,
+    synonyms::SynonymMap,
+    tokenizer::{CJK_TOKENIZER, ENGLISH_TOKENIZER},
+};
+
 /// 默认的写入器内存限制（50MB）
+#[cfg(feature = "server")]
 const DEFAULT_WRITER_MEMORY: usize = 50_000_000;
-/// 默认的搜索结果数量限制
-const DEFAULT_SEARCH_LIMIT: usize = 10;
+/// `sort=popular` 时文章热度（0 到 1）对得分的加成：`score * (1 + 热度 * 权重)`
+#[cfg(feature = "server")]
+const POPULARITY_WEIGHT: f32 = 0.5;
+/// `sort=popular` 时查询点击率的权重，按点击率加到被点击文章的得分上
+#[cfg(feature = "server")]
+const CLICK_SIGNAL_BOOST: f32 = 2.0;
 /// 默认的摘要长度（单词数）
+#[cfg(feature = "server")]
 const DEFAULT_EXCERPT_LENGTH: usize = 50;
+/// 摘要的最大字符数（中文没有空白分词时生效）
+#[cfg(feature = "server")]
+const MAX_EXCERPT_CHARS: usize = 200;
+/// 结果数少于该值时尝试给出拼写纠错建议
+#[cfg(feature = "server")]
+const LOW_RESULT_THRESHOLD: usize = 3;
+/// 单篇文章最多关联的译文数量
+#[cfg(feature = "server")]
+const MAX_TRANSLATIONS: usize = 16;
+/// 默认的相关文章数量
+pub const DEFAULT_RELATED_LIMIT: usize = 5;
+/// 相关文章查询最多使用的关键词数量
+#[cfg(feature = "server")]
+const RELATED_MAX_QUERY_TERMS: usize = 25;
+/// 相关文章查询中共同标签的权重
+#[cfg(feature = "server")]
+const RELATED_TAG_BOOST: f32 = 2.0;
 
 #[derive(Debug, Error)]
 pub enum SearchError {
+    #[cfg(feature = "server")]
     #[error("索引错误: {0}")]
     IndexError(#[from] TantivyError),
     #[error("解析错误: {0}")]
     ParseError(#[from] toml::de::Error),
     #[error("IO错误: {0}")]
     IoError(#[from] std::io::Error),
+    #[cfg(feature = "server")]
     #[error("查询解析错误: {0}")]
     QueryError(#[from] QueryParserError),
+    #[cfg(feature = "server")]
     #[error("目录错误: {0}")]
     DirectoryError(#[from] OpenDirectoryError),
     #[error("无效的文档格式")]
     InvalidDocument,
     #[error("字段不存在: {0}")]
     FieldNotFound(String),
+    #[error("索引结构与当前版本不一致，请重新运行索引器重建索引")]
+    SchemaMismatch,
+    #[error("无效的参数: {0}")]
+    InvalidParameter(String),
+    #[error("JSON错误: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("文档不存在: {0}")]
+    DocumentNotFound(String),
+    #[error("不支持的静态索引版本: {0}")]
+    UnsupportedArtifact(u32),
+    #[error("搜索超时")]
+    Timeout,
+    #[cfg(feature = "server")]
+    #[error("文件监视错误: {0}")]
+    WatchError(#[from] notify_debouncer_mini::notify::Error),
 }
 
 pub type SearchResult<T> = std::result::Result<T, SearchError>;
@@ -50,18 +173,142 @@ pub struct SearchDoc {
     pub excerpt: String,
     /// 文档标签
     pub tags: Vec<String>,
+    /// 文档语言代码
+    #[serde(default)]
+    pub lang: String,
+    /// 发布日期（`YYYY-MM-DD`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// 同一篇文章的其他语言版本
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub translations: Vec<Translation>,
+}
+
+/// 文章的另一种语言版本
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Translation {
+    /// 语言代码
+    pub lang: String,
+    /// 文档路径
+    pub path: String,
+    /// 文档标题
+    pub title: String,
+}
+
+/// 带拼写纠错建议的搜索结果
+#[derive(Serialize)]
+pub struct CorrectedSearch {
+    /// 搜索结果；自动纠错时为纠正后查询的结果
+    pub results: Vec<SearchDoc>,
+    /// 拼写纠错建议
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<SpellingSuggestion>,
+}
+
+#[cfg(feature = "server")]
+/// 按读取器代次缓存的派生数据
+///
+/// 读取器加载新的索引代次后，下一次访问会重新构建。
+struct GenerationCache<T> {
+    slot: RwLock<Option<(u64, Arc<T>)>>,
+}
+
+#[cfg(feature = "server")]
+impl<T> GenerationCache<T> {
+    fn new() -> Self {
+        GenerationCache { slot: RwLock::new(None) }
+    }
+
+    fn get_or_build<F>(&self, generation: u64, build: F) -> SearchResult<Arc<T>>
+    where
+        F: FnOnce() -> SearchResult<T>,
+    {
+        if let Some((cached_generation, value)) = self.slot.read().unwrap().as_ref() {
+            if *cached_generation == generation {
+                return Ok(Arc::clone(value));
+            }
+        }
+
+        let value = Arc::new(build()?);
+        *self.slot.write().unwrap() = Some((generation, Arc::clone(&value)));
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "server")]
+/// 当前生效的同义词表及其来源文件
+#[derive(Default)]
+struct SynonymSource {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    map: Arc<SynonymMap>,
+}
+
+#[cfg(feature = "server")]
+/// 某种语言的分析字段
+#[derive(Clone, Copy)]
+struct LanguageFields {
+    title: Field,
+    content: Field,
+}
+
+#[cfg(feature = "server")]
+impl LanguageFields {
+    fn add_to(schema_builder: &mut tantivy::schema::SchemaBuilder, suffix: &str, tokenizer: &str) -> Self {
+        let indexing = TextFieldIndexing::default()
+            .set_tokenizer(tokenizer)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        let options = TextOptions::default()
+            .set_indexing_options(indexing)
+            .set_stored();
+        LanguageFields {
+            title: schema_builder.add_text_field(&format!("title{}", suffix), options.clone()),
+            content: schema_builder.add_text_field(&format!("content{}", suffix), options),
+        }
+    }
 }
 
+/// 索引结构版本，修改 schema 后递增，由就绪检查报告
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[cfg(feature = "server")]
 /// 搜索引擎核心结构
 pub struct SearchEngine {
     index: Index,
+    /// 索引目录，用于读取最近一次提交的时间
+    index_path: PathBuf,
+    reader: IndexReader,
     schema: Schema,
-    title_field: tantivy::schema::Field,
-    content_field: tantivy::schema::Field,
-    path_field: tantivy::schema::Field,
-    tags_field: tantivy::schema::Field,
+    /// 中文及默认语言字段（CJK 分析器）
+    zh_fields: LanguageFields,
+    /// 英文字段（英文分析器）
+    en_fields: LanguageFields,
+    path_field: Field,
+    /// 标签分面，例如 `/rust`
+    tags_field: Field,
+    lang_field: Field,
+    /// 发布日期的 Unix 时间戳（秒），用于按日期排序
+    date_field: Field,
+    /// 去掉语言后缀的路径，同一篇文章的各语言版本取值相同
+    translation_key_field: Field,
+    /// 建立索引时的文章热度（0 到 1），用于 `sort=popular`
+    popularity_field: Field,
+    suggest_index: GenerationCache<SuggestIndex>,
+    spell_dictionary: GenerationCache<SpellDictionary>,
+    synonyms: RwLock<SynonymSource>,
+    /// 文章热度和查询点击率，见 [`SearchEngine::load_popularity`]
+    popularity: RwLock<Arc<Popularity>>,
+    /// 注册分词器时使用的分析器配置
+    analysis: AnalysisConfig,
+    /// 同时作为读取器的 warmer，在每次加载新提交时更新文档数和段数
+    metrics: Arc<Metrics>,
+    /// 搜索结果缓存，默认关闭，见 [`SearchEngine::configure_cache`]；同时作为 warmer 在加载新提交时清空
+    query_cache: Arc<QueryCache>,
+    /// 单次搜索收集结果的时间上限（毫秒），为 0 时不限制
+    search_timeout_ms: AtomicU64,
 }
 
+#[cfg(feature = "server")]
 impl SearchEngine {
     /// 创建新的搜索引擎实例
     /// 
@@ -71,11 +318,44 @@ impl SearchEngine {
     /// # Returns
     /// * `SearchResult<Self>` - 搜索引擎实例或错误
     pub fn new(index_path: &str) -> SearchResult<Self> {
+        Self::with_analysis(index_path, AnalysisConfig::default())
+    }
+
+    /// 删除已有索引并创建一个空索引
+    /// 
+    /// 索引结构变化后，用于从头重建索引。
+    /// 
+    /// # Arguments
+    /// * `index_path` - 索引文件存储路径
+    /// * `analysis` - 分析器配置
+    pub fn recreate(index_path: &str, analysis: AnalysisConfig) -> SearchResult<Self> {
+        let path = Path::new(index_path);
+        if path.exists() {
+            std::fs::remove_dir_all(path)?;
+        }
+        Self::with_analysis(index_path, analysis)
+    }
+
+    /// 使用指定的分析器配置创建搜索引擎实例
+    /// 
+    /// 分析器配置改变后需要重建索引，否则查询与索引的分词结果会不一致。
+    /// 
+    /// # Arguments
+    /// * `index_path` - 索引文件存储路径
+    /// * `analysis` - 分析器配置
+    /// 
+    /// # Returns
+    /// * `SearchResult<Self>` - 搜索引擎实例或错误
+    pub fn with_analysis(index_path: &str, analysis: AnalysisConfig) -> SearchResult<Self> {
         let mut schema_builder = Schema::builder();
-        let title_field = schema_builder.add_text_field("title", TEXT | STORED);
-        let content_field = schema_builder.add_text_field("content", TEXT);
-        let path_field = schema_builder.add_text_field("path", STORED);
-        let tags_field = schema_builder.add_text_field("tags", STORED);
+        let zh_fields = LanguageFields::add_to(&mut schema_builder, "", CJK_TOKENIZER);
+        let en_fields = LanguageFields::add_to(&mut schema_builder, "_en", ENGLISH_TOKENIZER);
+        let path_field = schema_builder.add_text_field("path", STRING | STORED);
+        let tags_field = schema_builder.add_facet_field("tags", FacetOptions::default().set_stored());
+        let lang_field = schema_builder.add_facet_field("lang", FacetOptions::default().set_stored());
+        let date_field = schema_builder.add_i64_field("date", INDEXED | STORED | FAST);
+        let translation_key_field = schema_builder.add_text_field("translation_key", STRING | STORED);
+        let popularity_field = schema_builder.add_f64_field("popularity", FAST);
         let schema = schema_builder.build();
         
         // 创建索引目录
@@ -93,22 +373,51 @@ impl SearchEngine {
             // 使用 create_in_dir 替代 create_with_settings
             Index::create_in_dir(index_path, schema.clone())?
         };
+        if index.schema() != schema {
+            return Err(SearchError::SchemaMismatch);
+        }
+        tokenizer::register(&index, &analysis);
         
-        // 确保索引可用
-        let _reader = index.reader()?;
+        // 读取器在提交后自动重新加载
+        let metrics = Arc::new(Metrics::new());
+        let query_cache = Arc::new(QueryCache::new(0, Duration::ZERO));
+        let warmers = vec![
+            Arc::downgrade(&metrics) as Weak<dyn Warmer>,
+            Arc::downgrade(&query_cache) as Weak<dyn Warmer>,
+        ];
+        let reader = index.reader_builder().warmers(warmers).try_into()?;
         
         Ok(SearchEngine { 
             index,
+            index_path: index_path.to_path_buf(),
+            reader,
             schema,
-            title_field,
-            content_field,
+            zh_fields,
+            en_fields,
             path_field,
             tags_field,
+            lang_field,
+            date_field,
+            translation_key_field,
+            popularity_field,
+            suggest_index: GenerationCache::new(),
+            spell_dictionary: GenerationCache::new(),
+            synonyms: RwLock::new(SynonymSource::default()),
+            popularity: RwLock::new(Arc::new(Popularity::default())),
+            analysis,
+            metrics,
+            query_cache,
+            search_timeout_ms: AtomicU64::new(0),
         })
     }
 
     /// 索引一篇文档
     /// 
+    /// 文档语言依次取自 front matter 中的 `lang`、文件名的语言后缀
+    /// （Zola 风格的 `index.en.md`），都没有时根据正文自动识别；
+    /// 英文文档写入英文分析字段，其余文档写入 CJK 分析字段。
+    /// 去掉语言后缀后路径相同的文档互为译文。
+    /// 
     /// # Arguments
     /// * `content` - 文档内容，包含 front matter
     /// * `file_path` - 文档路径
@@ -116,13 +425,154 @@ impl SearchEngine {
     /// # Returns
     /// * `SearchResult<()>` - 成功或错误
     pub fn index_document(&self, content: &str, file_path: &Path) -> SearchResult<()> {
+        let started = Instant::now();
+        let document = self.build_document(content, file_path)?;
         let mut writer = self.index.writer(DEFAULT_WRITER_MEMORY)?;
+        writer.add_document(document)?;
+        
+        writer.commit()?;
         
+        // 重新加载读取器，使新文档立即可见
+        self.reader.reload()?;
+        self.metrics.observe_indexing(started.elapsed());
+        
+        Ok(())
+    }
+
+    /// 索引一篇文档，替换索引中相同路径的旧版本
+    ///
+    /// 删除和写入在同一次提交中完成，读取器不会看到文档缺失的中间状态。
+    /// 返回索引中原来是否有该路径的文档。
+    pub fn upsert_document(&self, content: &str, file_path: &Path) -> SearchResult<bool> {
+        let started = Instant::now();
+        let document = self.build_document(content, file_path)?;
+        let path = file_path.to_string_lossy();
+        let replaced = self.contains(&path)?;
+        let mut writer = self.index.writer(DEFAULT_WRITER_MEMORY)?;
+        writer.delete_term(Term::from_field_text(self.path_field, &path));
+        writer.add_document(document)?;
+        writer.commit()?;
+        self.reader.reload()?;
+        self.metrics.observe_indexing(started.elapsed());
+        Ok(replaced)
+    }
+
+    /// 删除路径为 `path` 的文档，返回索引中原来是否有该文档
+    pub fn delete_document(&self, path: &str) -> SearchResult<bool> {
+        if !self.contains(path)? {
+            return Ok(false);
+        }
+        let mut writer = self.index.writer(DEFAULT_WRITER_MEMORY)?;
+        writer.delete_term(Term::from_field_text(self.path_field, path));
+        writer.commit()?;
+        self.reader.reload()?;
+        Ok(true)
+    }
+
+    /// 用目录（包括子目录）中的所有 `.md` 文件替换索引内容，返回索引的文档数
+    ///
+    /// 先解析全部文件，有文件无法解析时返回错误且索引保持不变；
+    /// 清空和写入在同一次提交中完成，重建期间搜索照常返回旧的结果。
+    pub fn reindex<P: AsRef<Path>>(&self, content_dir: P) -> SearchResult<usize> {
+        let started = Instant::now();
+        let mut documents = Vec::new();
+        for entry in walkdir::WalkDir::new(content_dir) {
+            let entry = entry.map_err(|e| SearchError::IoError(e.into()))?;
+            if entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "md") {
+                let content = std::fs::read_to_string(entry.path())?;
+                documents.push(self.build_document(&content, entry.path())?);
+            }
+        }
+
+        let count = documents.len();
+        let mut writer = self.index.writer(DEFAULT_WRITER_MEMORY)?;
+        writer.delete_all_documents()?;
+        for document in documents {
+            writer.add_document(document)?;
+        }
+        writer.commit()?;
+        self.reader.reload()?;
+        self.metrics.observe_indexing(started.elapsed());
+        Ok(count)
+    }
+
+    /// 把目录（包括子目录）中的所有 `.md` 文件写入或替换到索引中，见 [`SearchEngine::sync_documents`]
+    ///
+    /// 与 [`SearchEngine::reindex`] 不同，不删除目录之外的文档，例如通过管理接口上传的文档。
+    pub fn sync_directory<P: AsRef<Path>>(&self, content_dir: P) -> SearchResult<SyncSummary> {
+        let mut paths = Vec::new();
+        for entry in walkdir::WalkDir::new(content_dir) {
+            let entry = entry.map_err(|e| SearchError::IoError(e.into()))?;
+            if entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "md") {
+                paths.push(entry.into_path());
+            }
+        }
+        self.sync_documents(&paths)
+    }
+
+    /// 按磁盘上的文件同步文档：文件存在时写入或替换，文件已删除时从索引中删除
+    ///
+    /// 所有修改在同一次提交中完成。无法读取或解析的文件保留索引中原来的版本，
+    /// 记录在 [`SyncSummary::skipped`] 中，不影响其他文件。
+    pub fn sync_documents<P: AsRef<Path>>(&self, paths: &[P]) -> SearchResult<SyncSummary> {
+        let started = Instant::now();
+        let mut summary = SyncSummary::default();
+        let mut writer = self.index.writer(DEFAULT_WRITER_MEMORY)?;
+        for path in paths {
+            let path = path.as_ref();
+            let key = path.to_string_lossy().to_string();
+            let document = match std::fs::read_to_string(path) {
+                Ok(content) => self.build_document(&content, path).map(Some),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            };
+            match document {
+                Ok(Some(document)) => {
+                    writer.delete_term(Term::from_field_text(self.path_field, &key));
+                    writer.add_document(document)?;
+                    summary.upserted.push(key);
+                }
+                Ok(None) if self.contains(&key)? => {
+                    writer.delete_term(Term::from_field_text(self.path_field, &key));
+                    summary.deleted.push(key);
+                }
+                Ok(None) => {}
+                Err(e) => summary.skipped.push(SkippedDocument {
+                    path: key,
+                    error: e.to_string(),
+                }),
+            }
+        }
+        if summary.changed() {
+            writer.commit()?;
+            self.reader.reload()?;
+            self.metrics.observe_indexing(started.elapsed());
+        }
+        Ok(summary)
+    }
+
+    /// 把所有段合并为一个并清理不再使用的文件，返回合并前的段数
+    ///
+    /// 多次增量更新之后段数增加、已删除的文档仍占用空间，合并后搜索更快。
+    pub fn optimize(&self) -> SearchResult<usize> {
+        let segment_ids = self.index.searchable_segment_ids()?;
+        let mut writer = self.index.writer(DEFAULT_WRITER_MEMORY)?;
+        if segment_ids.len() > 1 {
+            writer.merge(&segment_ids).wait()?;
+        }
+        writer.garbage_collect_files().wait()?;
+        writer.wait_merging_threads()?;
+        self.reader.reload()?;
+        Ok(segment_ids.len())
+    }
+
+    /// 解析文档的 front matter 并生成 tantivy 文档
+    fn build_document(&self, content: &str, file_path: &Path) -> SearchResult<Document> {
         // 解析 Markdown 文件的 front matter
-        let (front_matter, content) = if let Some(_) = content.strip_prefix("+++") {
-            if let Some(end) = content[3..].find("+++") {
-                let front_matter = &content[3..end + 3];
-                let content = &content[end + 6..];
+        let (front_matter, content) = if let Some(rest) = content.strip_prefix("+++") {
+            if let Some(end) = rest.find("+++") {
+                let front_matter = &rest[..end];
+                let content = &rest[end + 3..];
                 (front_matter.to_string(), content.to_string())
             } else {
                 return Err(SearchError::InvalidDocument);
@@ -139,284 +589,2241 @@ impl SearchEngine {
             .and_then(|v| v.as_str())
             .ok_or_else(|| SearchError::FieldNotFound("title".to_string()))?;
             
-        let tags = front_matter
+        let tags: Vec<&str> = front_matter
             .get("taxonomies")
             .and_then(|v| v.get("tags"))
             .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str())
-                    .collect::<Vec<_>>()
-                    .join(",")
-            })
+            .map(|arr| arr.iter().filter_map(|v| v.as_str()).filter(|t| !t.is_empty()).collect())
             .unwrap_or_default();
+        let date = front_matter.get("date").and_then(parse_date);
 
-        writer.add_document(doc!(
-            self.title_field => title,
-            self.content_field => content,
-            self.path_field => file_path.to_string_lossy().to_string(),
-            self.tags_field => tags
-        ))?;
-        
-        writer.commit()?;
-        
-        // 重新加载读取器
-        let _reader = self.index.reader()?;
-        
-        Ok(())
+        let (translation_key, suffix_language) = split_language_suffix(file_path);
+        let language = front_matter
+            .get("lang")
+            .and_then(|v| v.as_str())
+            .and_then(Language::from_code)
+            .or(suffix_language)
+            .unwrap_or_else(|| detect_language(&format!("{}\n{}", title, content)));
+        let fields = self.language_fields(language);
+        let path = file_path.to_string_lossy().to_string();
+        let popularity = self.popularity.read().unwrap().post(&path);
+
+        let mut document = doc!(
+            fields.title => title,
+            fields.content => content,
+            self.popularity_field => popularity,
+            self.path_field => path,
+            self.lang_field => language_facet(language),
+            self.translation_key_field => translation_key
+        );
+        for tag in tags {
+            document.add_facet(self.tags_field, Facet::from_path([tag]));
+        }
+        if let Some(date) = date {
+            document.add_i64(self.date_field, date);
+        }
+        Ok(document)
     }
 
     /// 搜索文档
     /// 
+    /// 支持短语查询（`"error handling"`）和邻近查询（`"error handling"~3`，
+    /// 词项之间最多相隔 3 个位置）。不带引号的多词查询会自动为词项
+    /// 彼此靠近的文档加分。
+    /// 
     /// # Arguments
     /// * `query_str` - 搜索查询字符串
     /// 
     /// # Returns
     /// * `SearchResult<Vec<SearchDoc>>` - 搜索结果或错误
     pub fn search(&self, query_str: &str) -> SearchResult<Vec<SearchDoc>> {
-        if query_str.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        let reader = self.index.reader()?;
-        let searcher = reader.searcher();
-        
-        let query_parser = QueryParser::for_index(
-            &self.index,
-            vec![self.title_field, self.content_field],
-        );
-        
-        let query = query_parser.parse_query(query_str)?;
-        let top_docs = searcher.search(&query, &TopDocs::with_limit(DEFAULT_SEARCH_LIMIT))?;
-        
-        let mut results = Vec::new();
-        for (_score, doc_address) in top_docs {
-            let doc = searcher.doc(doc_address)?;
-            
-            let result = SearchDoc {
-                title: doc.get_first(self.title_field)
-                    .and_then(|f| f.as_text())
-                    .unwrap_or("")
-                    .to_string(),
-                path: doc.get_first(self.path_field)
-                    .and_then(|f| f.as_text())
-                    .unwrap_or("")
-                    .to_string(),
-                excerpt: doc.get_first(self.content_field)
-                    .and_then(|f| f.as_text())
-                    .map(|content| {
-                        // 使用常量定义摘要长度
-                        let words: Vec<&str> = content.split_whitespace().collect();
-                        if words.len() > DEFAULT_EXCERPT_LENGTH {
-                            words[..DEFAULT_EXCERPT_LENGTH].join(" ") + "..."
-                        } else {
-                            words.join(" ")
-                        }
-                    })
-                    .unwrap_or_default(),
-                tags: doc.get_first(self.tags_field)
-                    .and_then(|f| f.as_text())
-                    .map(|t| t.split(',').map(|s| s.to_string()).collect())
-                    .unwrap_or_default(),
-            };
-            results.push(result);
-        }
-        
-        Ok(results)
+        self.search_in_language(query_str, None)
     }
 
-    /// 清空索引
+    /// 在指定语言的文档中搜索
     /// 
-    /// 删除索引中的所有文档。这个操作不可撤销。
+    /// # Arguments
+    /// * `query_str` - 搜索查询字符串
+    /// * `language` - 只返回该语言的文档；为 `None` 时不限语言
     /// 
     /// # Returns
-    /// * `SearchResult<()>` - 成功或错误
-    pub fn clear(&self) -> SearchResult<()> {
-        let mut writer = self.index.writer(DEFAULT_WRITER_MEMORY)?;
-        writer.delete_all_documents()?;
-        writer.commit()?;
-        Ok(())
+    /// * `SearchResult<Vec<SearchDoc>>` - 搜索结果或错误
+    pub fn search_in_language(
+        &self,
+        query_str: &str,
+        language: Option<Language>,
+    ) -> SearchResult<Vec<SearchDoc>> {
+        let mut builder = SearchRequest::builder(query_str);
+        if let Some(language) = language {
+            builder = builder.lang(language);
+        }
+        let response = self.execute(&builder.build())?;
+        Ok(response.hits.into_iter().map(SearchDoc::from).collect())
     }
 
-    /// 获取索引中的文档数量
+    /// 执行结构化的搜索请求
+    /// 
+    /// 请求开启 `correct_spelling` 且匹配总数过少时附带拼写纠错建议；
+    /// 原查询没有结果而纠正后的查询有结果时，直接返回纠正后查询的结果。
+    /// 
+    /// # Arguments
+    /// * `request` - 搜索请求
     /// 
     /// # Returns
-    /// * `SearchResult<u64>` - 文档数量或错误
-    pub fn doc_count(&self) -> SearchResult<u64> {
-        let reader = self.index.reader()?;
-        let searcher = reader.searcher();
-        Ok(searcher.num_docs())
+    /// * `SearchResult<SearchResponse>` - 搜索响应或错误
+    pub fn execute(&self, request: &SearchRequest) -> SearchResult<SearchResponse> {
+        self.execute_on(&self.reader.searcher(), request)
     }
 
-    /// 检查索引是否存在
-    pub fn exists(&self) -> bool {
-        self.index.reader().is_ok()
-    }
+    /// 先查询结果缓存，未命中时执行请求并写入缓存
+    ///
+    /// 缓存关闭或 `bypass` 为真时直接执行，不读取也不写入缓存。命中时 `took_ms`
+    /// 为查找缓存的耗时，查询数和查询耗时指标只统计实际执行的查询。
+    ///
+    /// # Arguments
+    /// * `request` - 搜索请求
+    /// * `bypass` - 是否跳过缓存
+    ///
+    /// # Returns
+    /// * `SearchResult<(SearchResponse, CacheStatus)>` - 搜索响应和缓存状态或错误
+    pub fn execute_cached(
+        &self,
+        request: &SearchRequest,
+        bypass: bool,
+    ) -> SearchResult<(SearchResponse, CacheStatus)> {
+        if bypass || !self.query_cache.is_enabled() {
+            return Ok((self.execute(request)?, CacheStatus::Bypass));
+        }
 
-    /// 获取索引统计信息
-    pub fn stats(&self) -> SearchResult<IndexStats> {
-        let reader = self.index.reader()?;
-        let searcher = reader.searcher();
-        Ok(IndexStats {
-            doc_count: searcher.num_docs(),
-            field_count: self.schema.fields().count() as u64,
-        })
+        let started = Instant::now();
+        let searcher = self.reader.searcher();
+        let generation = searcher.generation().generation_id();
+        let key = CacheKey::new(request);
+        if let Some(mut response) = self.query_cache.get(&key, generation) {
+            self.metrics.cache_hit();
+            response.took_ms = started.elapsed().as_millis() as u64;
+            return Ok((response, CacheStatus::Hit));
+        }
+
+        self.metrics.cache_miss();
+        let response = self.execute_on(&searcher, request)?;
+        self.query_cache.insert(key, generation, response.clone());
+        Ok((response, CacheStatus::Miss))
     }
-}
 
-#[derive(Debug, Serialize)]
-pub struct IndexStats {
-    pub doc_count: u64,
-    pub field_count: u64,
-}
+    /// 设置结果缓存的容量和 TTL 并清空缓存；容量为 0 时关闭缓存
+    pub fn configure_cache(&self, capacity: usize, ttl: Duration) {
+        self.query_cache.configure(capacity, ttl);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use std::path::PathBuf;
+    pub fn query_cache(&self) -> &QueryCache {
+        &self.query_cache
+    }
 
-    /// 清理重试次数
-    const CLEANUP_RETRY_COUNT: u32 = 3;
-    /// 清理重试间隔（毫秒）
-    const CLEANUP_RETRY_INTERVAL: u64 = 100;
+    /// 决定搜索结果的内容版本，用于生成 HTTP `ETag`
+    ///
+    /// 由当前读取器中的段及其删除标记、热度文件的生成时间和同义词文件的修改时间计算；
+    /// 段 ID 保存在索引中，且使用 SHA-256 而不是随 Rust 版本变化的标准库哈希，
+    /// 因此打开同一份索引的不同进程和不同构建得到相同的版本。
+    pub fn content_version(&self) -> String {
+        let mut hasher = Sha256::new();
+        for (segment_id, delete_opstamp) in self.reader.searcher().generation().segments() {
+            hasher.update(segment_id.uuid_string());
+            match delete_opstamp {
+                Some(opstamp) => hasher.update(opstamp.to_le_bytes()),
+                None => hasher.update([0xff]),
+            }
+        }
+        hasher.update(self.popularity.read().unwrap().generated_at.to_le_bytes());
+        let modified = self.synonyms.read().unwrap().modified;
+        let modified = modified.and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok());
+        hasher.update(modified.map_or(u128::MAX, |elapsed| elapsed.as_nanos()).to_le_bytes());
+        short_digest(hasher)
+    }
 
-    mod setup {
-        use super::*;
+    /// 设置单次搜索收集结果的时间上限；超时的搜索返回 `SearchError::Timeout`
+    ///
+    /// 只限制匹配文档的遍历，不包括读取文档和生成高亮片段。
+    pub fn set_search_timeout(&self, timeout: Option<Duration>) {
+        let millis = timeout.map_or(0, |timeout| timeout.as_millis().clamp(1, u64::MAX as u128) as u64);
+        self.search_timeout_ms.store(millis, Ordering::Relaxed);
+    }
 
-        pub(crate) fn setup_test_dir(name: &str) -> SearchResult<PathBuf> {
-            let test_dir = PathBuf::from("target").join("test_indexes").join(name);
-            if test_dir.exists() {
-                cleanup_test_dir(&test_dir)?;
+    fn execute_on(&self, searcher: &Searcher, request: &SearchRequest) -> SearchResult<SearchResponse> {
+        let started = Instant::now();
+        let deadline = match self.search_timeout_ms.load(Ordering::Relaxed) {
+            0 => None,
+            millis => Some(started + Duration::from_millis(millis)),
+        };
+        let mut response = match self.run(searcher, request, &request.query, deadline) {
+            Ok(response) => response,
+            Err(e) => {
+                match e {
+                    SearchError::QueryError(_) => self.metrics.query_parse_error(),
+                    SearchError::Timeout => self.metrics.search_timeout(),
+                    _ => {}
+                }
+                return Err(e);
             }
-            fs::create_dir_all(&test_dir)?;
-            Ok(test_dir)
-        }
+        };
 
-        pub(crate) fn cleanup_test_dir(dir: &Path) -> SearchResult<()> {
-            if dir.exists() {
-                for _ in 0..CLEANUP_RETRY_COUNT {
-                    match fs::remove_dir_all(dir) {
-                        Ok(_) => break,
-                        Err(e) if e.kind() == std::io::ErrorKind::DirectoryNotEmpty => {
-                            std::thread::sleep(std::time::Duration::from_millis(CLEANUP_RETRY_INTERVAL));
-                            continue;
-                        }
-                        Err(e) => return Err(e.into()),
+        if request.correct_spelling && response.total < LOW_RESULT_THRESHOLD {
+            if let Some(corrected) = self.correct_query(&request.query)? {
+                let corrected_response = self.run(searcher, request, &corrected, deadline)?;
+                if corrected_response.total > response.total {
+                    let auto_corrected = response.total == 0;
+                    if auto_corrected {
+                        response = corrected_response;
                     }
+                    response.suggestion = Some(SpellingSuggestion {
+                        suggestion: corrected,
+                        auto_corrected,
+                    });
                 }
             }
-            Ok(())
         }
+
+        let elapsed = started.elapsed();
+        self.metrics.observe_query(elapsed, response.total);
+        response.took_ms = elapsed.as_millis() as u64;
+        Ok(response)
     }
 
-    mod indexing {
-        use super::*;
-        use super::setup::*;
+    /// 用给定的查询字符串执行请求中的其余部分
+    fn run(
+        &self,
+        searcher: &Searcher,
+        request: &SearchRequest,
+        query_str: &str,
+        deadline: Option<Instant>,
+    ) -> SearchResult<SearchResponse> {
+        if query_str.trim().is_empty() {
+            return Ok(SearchResponse::default());
+        }
 
-        #[test]
-        fn test_document_indexing() -> SearchResult<()> {
-            let test_dir = setup_test_dir("index_doc")?;
-            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
-            
-            let content = r#"+++
-            title = "Test Document"
-            [taxonomies]
-            tags = ["test", "rust"]
-            +++
-            This is a test document."#;
-            
-            engine.index_document(content, &PathBuf::from("test.md"))?;
-            
-            let results = engine.search("test")?;
-            assert_eq!(results.len(), 1);
-            assert_eq!(results[0].title, "Test Document");
-            
-            cleanup_test_dir(&test_dir)?;
-            Ok(())
+        let mut query = self.build_query(query_str, &request.filters)?;
+        if request.sort == SortOrder::Popular {
+            query = self.with_click_signals(query, query_str);
+        }
+        let (total, mut top_docs) = self.top_docs(searcher, query.as_ref(), request, deadline)?;
+        if let Some(min_score) = request.min_score {
+            top_docs.retain(|(score, _)| *score >= min_score);
         }
 
-        #[test]
-        fn test_invalid_front_matter() -> SearchResult<()> {
-            let test_dir = setup_test_dir("index_invalid")?;
-            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
-            
-            let content = r#"+++
-            invalid toml content
-            +++
-            Test content"#;
-            
-            assert!(engine.index_document(content, &PathBuf::from("invalid.md")).is_err());
-            
-            cleanup_test_dir(&test_dir)?;
-            Ok(())
+        let mut facets = BTreeMap::new();
+        for &facet in &request.facets {
+            facets.insert(facet.name().to_string(), self.facet_counts(searcher, query.as_ref(), facet, deadline)?);
         }
-    }
 
-    mod searching {
-        use super::*;
-        use super::setup::*;
+        let highlighters = match request.highlight {
+            Some(options) => Some(self.snippet_generators(searcher, query.as_ref(), options)?),
+            None => None,
+        };
 
-        #[test]
-        fn test_search_with_empty_query() -> SearchResult<()> {
-            let test_dir = setup_test_dir("index_empty")?;
-            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
-            
-            let results = engine.search("")?;
-            assert!(results.is_empty());
-            
-            cleanup_test_dir(&test_dir)?;
-            Ok(())
+        let mut hits = Vec::with_capacity(top_docs.len());
+        for (score, doc_address) in top_docs {
+            let doc = searcher.doc(doc_address)?;
+            let path = self.doc_path(&doc).to_string();
+            let language = self.doc_language(&doc);
+            let translations = if request.wants(HitField::Translations) {
+                Some(self.translations_of(searcher, &doc, &path)?)
+            } else {
+                None
+            };
+            let explanation = if request.explain {
+                Some(serde_json::to_value(query.explain(searcher, doc_address)?)?)
+            } else {
+                None
+            };
+            let highlight = highlighters.as_ref().and_then(|(zh, en)| {
+                let generator = match language {
+                    Language::Zh => zh,
+                    Language::En => en,
+                };
+                let snippet = generator.snippet_from_doc(&doc);
+                (!snippet.highlighted().is_empty()).then(|| snippet.to_html())
+            });
+
+            hits.push(SearchHit {
+                score,
+                title: request.wants(HitField::Title).then(|| self.doc_title(&doc).to_string()),
+                excerpt: request.wants(HitField::Excerpt).then(|| excerpt(self.doc_content(&doc))),
+                tags: request.wants(HitField::Tags).then(|| self.doc_tags(&doc)),
+                lang: request.wants(HitField::Lang).then(|| language.code().to_string()),
+                date: request.wants(HitField::Date).then(|| self.doc_date(&doc)).flatten(),
+                translations,
+                highlight,
+                explanation,
+                path,
+            });
         }
 
-        #[test]
-        fn test_search_with_limit() -> SearchResult<()> {
+        Ok(SearchResponse {
+            hits,
+            total,
+            facets,
+            took_ms: 0,
+            suggestion: None,
+        })
+    }
+
+    /// 解释一篇文档在查询下的得分，用于排查排序问题
+    /// 
+    /// # Arguments
+    /// * `query_str` - 搜索查询字符串
+    /// * `path` - 文档路径，与索引时的路径一致
+    /// 
+    /// # Returns
+    /// * `SearchResult<Option<Explanation>>` - 得分的计算过程；文档不匹配查询时为 `None`，
+    ///   索引中没有该路径时返回 `SearchError::DocumentNotFound`
+    pub fn explain(&self, query_str: &str, path: &str) -> SearchResult<Option<Explanation>> {
+        let searcher = self.reader.searcher();
+        self.doc_address_of(&searcher, path)?;
+
+        let query = self.build_query(query_str, &[])?;
+        let matching = BooleanQuery::new(vec![
+            (Occur::Must, query.box_clone()),
+            (Occur::Must, Box::new(self.path_query(path))),
+        ]);
+        match searcher.search(&matching, &TopDocs::with_limit(1))?.first() {
+            Some((_, doc_address)) => Ok(Some(query.explain(&searcher, *doc_address)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 与某篇文章内容相似的其他文章
+    /// 
+    /// 用 `MoreLikeThisQuery` 从文章的标题和正文中选取关键词，并为共同标签加分；
+    /// 结果不包含文章本身及其译文。
+    /// 
+    /// # Arguments
+    /// * `path` - 文档路径，与索引时的路径一致
+    /// * `limit` - 最多返回的文章数量
+    /// 
+    /// # Returns
+    /// * `SearchResult<Vec<SearchHit>>` - 相关文章（不含正文摘要和译文）或错误
+    pub fn related(&self, path: &str, limit: usize) -> SearchResult<Vec<SearchHit>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let searcher = self.reader.searcher();
+        let doc = searcher.doc(self.doc_address_of(&searcher, path)?)?;
+
+        let text_fields = [
+            self.zh_fields.title,
+            self.zh_fields.content,
+            self.en_fields.title,
+            self.en_fields.content,
+        ];
+        let doc_fields: Vec<_> = text_fields
+            .iter()
+            .map(|&field| (field, doc.get_all(field).cloned().collect::<Vec<_>>()))
+            .filter(|(_, values)| !values.is_empty())
+            .collect();
+        let more_like_this = MoreLikeThisQuery::builder()
+            .with_min_doc_frequency(1)
+            .with_min_term_frequency(1)
+            .with_min_word_length(2)
+            .with_max_query_terms(RELATED_MAX_QUERY_TERMS)
+            .with_document_fields(doc_fields);
+
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Should, Box::new(more_like_this))];
+        for tag in self.doc_tags(&doc) {
+            let tag_query = TermQuery::new(
+                Term::from_facet(self.tags_field, &Facet::from_path([tag])),
+                IndexRecordOption::Basic,
+            );
+            clauses.push((Occur::Should, Box::new(BoostQuery::new(Box::new(tag_query), RELATED_TAG_BOOST))));
+        }
+        clauses.push((Occur::MustNot, Box::new(self.path_query(path))));
+        if let Some(key) = doc.get_first(self.translation_key_field).and_then(|f| f.as_text()) {
+            let translations = TermQuery::new(
+                Term::from_field_text(self.translation_key_field, key),
+                IndexRecordOption::Basic,
+            );
+            clauses.push((Occur::MustNot, Box::new(translations)));
+        }
+
+        let top_docs = searcher.search(&BooleanQuery::new(clauses), &TopDocs::with_limit(limit))?;
+        let mut related = Vec::with_capacity(top_docs.len());
+        for (score, doc_address) in top_docs {
+            let doc = searcher.doc(doc_address)?;
+            related.push(SearchHit {
+                score,
+                path: self.doc_path(&doc).to_string(),
+                title: Some(self.doc_title(&doc).to_string()),
+                tags: Some(self.doc_tags(&doc)),
+                lang: Some(self.doc_language(&doc).code().to_string()),
+                date: self.doc_date(&doc),
+                ..SearchHit::default()
+            });
+        }
+        Ok(related)
+    }
+
+    /// 导出供浏览器端离线搜索使用的静态索引
+    /// 
+    /// 词项由与索引相同的分析器生成，文档按路径排序，保证多次导出结果一致。
+    pub fn export_artifact(&self) -> SearchResult<SearchArtifact> {
+        let searcher = self.reader.searcher();
+        let mut builder = ArtifactBuilder::new(self.analysis.clone());
+        for_each_doc(&searcher, |doc| {
+            let artifact_doc = ArtifactDoc {
+                path: self.doc_path(doc).to_string(),
+                title: self.doc_title(doc).to_string(),
+                excerpt: excerpt(self.doc_content(doc)),
+                tags: self.doc_tags(doc),
+                lang: self.doc_language(doc).code().to_string(),
+                date: self.doc_date(doc),
+                field_lens: [0, 0],
+            };
+            builder.add_document(artifact_doc, self.doc_content(doc));
+        })?;
+        Ok(builder.build())
+    }
+
+    /// 索引中所有文档的路径，按路径排序
+    pub fn document_paths(&self) -> SearchResult<Vec<String>> {
+        let searcher = self.reader.searcher();
+        let mut paths = Vec::new();
+        for_each_doc(&searcher, |doc| paths.push(self.doc_path(doc).to_string()))?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn path_query(&self, path: &str) -> TermQuery {
+        TermQuery::new(Term::from_field_text(self.path_field, path), IndexRecordOption::Basic)
+    }
+
+    /// 索引中是否有该路径的文档
+    pub fn contains(&self, path: &str) -> SearchResult<bool> {
+        match self.doc_address_of(&self.reader.searcher(), path) {
+            Ok(_) => Ok(true),
+            Err(SearchError::DocumentNotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 为查询下被读者点击过的文章加分，加分与点击率成正比
+    fn with_click_signals(&self, query: Box<dyn Query>, query_str: &str) -> Box<dyn Query> {
+        let popularity = Arc::clone(&self.popularity.read().unwrap());
+        let signals = match popularity.query_signals(&query_log::anonymize_query(query_str)) {
+            Some(signals) => signals,
+            None => return query,
+        };
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, query)];
+        for (path, click_rate) in signals {
+            let boost = CLICK_SIGNAL_BOOST * *click_rate as f32;
+            clauses.push((Occur::Should, Box::new(BoostQuery::new(Box::new(self.path_query(path)), boost))));
+        }
+        Box::new(BooleanQuery::new(clauses))
+    }
+
+    /// 按路径查找文档
+    fn doc_address_of(&self, searcher: &Searcher, path: &str) -> SearchResult<DocAddress> {
+        searcher
+            .search(&self.path_query(path), &TopDocs::with_limit(1))?
+            .first()
+            .map(|(_, doc_address)| *doc_address)
+            .ok_or_else(|| SearchError::DocumentNotFound(path.to_string()))
+    }
+
+    fn query_parser(&self) -> QueryParser {
+        QueryParser::for_index(
+            &self.index,
+            vec![
+                self.zh_fields.title,
+                self.zh_fields.content,
+                self.en_fields.title,
+                self.en_fields.content,
+            ],
+        )
+    }
+
+    /// 解析查询字符串并加上过滤条件
+    fn build_query(&self, query_str: &str, filters: &[Filter]) -> SearchResult<Box<dyn Query>> {
+        let query_parser = self.query_parser();
+        let mut query: Box<dyn Query> = query_parser.parse_query(&self.expand_query(query_str))?;
+        // 多词查询：词项彼此靠近的文档额外加分
+        if let Some(phrase) = proximity_phrase(query_str) {
+            if let Ok(proximity_query) = query_parser.parse_query(&phrase) {
+                query = Box::new(BooleanQuery::new(vec![
+                    (Occur::Must, query),
+                    (
+                        Occur::Should,
+                        Box::new(BoostQuery::new(proximity_query, PROXIMITY_BOOST)),
+                    ),
+                ]));
+            }
+        }
+
+        if filters.is_empty() {
+            return Ok(query);
+        }
+        let mut clauses = vec![(Occur::Must, query)];
+        for filter in filters {
+            let term = match filter {
+                Filter::Tag(tag) => Term::from_facet(self.tags_field, &Facet::from_path([tag])),
+                Filter::Lang(language) => Term::from_facet(self.lang_field, &language_facet(*language)),
+            };
+            let filter_query: Box<dyn Query> = Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+            clauses.push((Occur::Must, filter_query));
+        }
+        Ok(Box::new(BooleanQuery::new(clauses)))
+    }
+
+    /// 按请求的排序方式和分页取出结果，同时统计匹配总数
+    fn top_docs(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        request: &SearchRequest,
+        deadline: Option<Instant>,
+    ) -> SearchResult<(usize, Vec<(Score, DocAddress)>)> {
+        let top = TopDocs::with_limit(request.limit.max(1)).and_offset(request.offset);
+        let (total, mut top_docs) = match request.sort {
+            SortOrder::Relevance => collect_within(searcher, query, &(Count, top), deadline)?,
+            SortOrder::Popular => {
+                let popularity_field = self.popularity_field;
+                let blended = top.tweak_score(move |segment_reader: &SegmentReader| {
+                    let popularity = segment_reader
+                        .fast_fields()
+                        .f64(popularity_field)
+                        .expect("popularity 是快速字段");
+                    move |doc: DocId, score: Score| {
+                        score * (1.0 + POPULARITY_WEIGHT * popularity.get_val(doc) as f32)
+                    }
+                });
+                collect_within(searcher, query, &(Count, blended), deadline)?
+            }
+            SortOrder::Newest | SortOrder::Oldest => {
+                let newest = request.sort == SortOrder::Newest;
+                let date_field = self.date_field;
+                let by_date = top.tweak_score(move |segment_reader: &SegmentReader| {
+                    let dates = segment_reader
+                        .fast_fields()
+                        .i64(date_field)
+                        .expect("date 是快速字段");
+                    move |doc: DocId, score: Score| {
+                        // 没有日期的文档时间戳为 0，排在最后
+                        let date = dates.get_val(doc);
+                        let key = match date {
+                            0 => i64::MIN,
+                            _ if newest => date,
+                            _ => -date,
+                        };
+                        (key, score)
+                    }
+                });
+                let (total, top_docs) = collect_within(searcher, query, &(Count, by_date), deadline)?;
+                let top_docs = top_docs
+                    .into_iter()
+                    .map(|((_, score), doc_address)| (score, doc_address))
+                    .collect();
+                (total, top_docs)
+            }
+        };
+        if request.limit == 0 {
+            top_docs.clear();
+        }
+        Ok((total, top_docs))
+    }
+
+    /// 统计分面中各取值的文档数
+    fn facet_counts(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        facet: FacetField,
+        deadline: Option<Instant>,
+    ) -> SearchResult<Vec<FacetCount>> {
+        let field = match facet {
+            FacetField::Tags => self.tags_field,
+            FacetField::Lang => self.lang_field,
+        };
+        let mut collector = FacetCollector::for_field(field);
+        collector.add_facet(Facet::root());
+        let counts = collect_within(searcher, query, &collector, deadline)?;
+
+        let mut values: Vec<FacetCount> = counts
+            .get(Facet::root())
+            .map(|(facet, count)| FacetCount {
+                value: facet.to_path().last().copied().unwrap_or_default().to_string(),
+                count,
+            })
+            .collect();
+        values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        Ok(values)
+    }
+
+    /// 为两种语言的正文字段创建高亮片段生成器
+    fn snippet_generators(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        options: HighlightOptions,
+    ) -> SearchResult<(SnippetGenerator, SnippetGenerator)> {
+        let create = |field| -> SearchResult<SnippetGenerator> {
+            let mut generator = SnippetGenerator::create(searcher, query, field)?;
+            generator.set_max_num_chars(options.max_chars);
+            Ok(generator)
+        };
+        Ok((create(self.zh_fields.content)?, create(self.en_fields.content)?))
+    }
+
+    /// 查找与文档互为译文的其他文档
+    fn translations_of(
+        &self,
+        searcher: &Searcher,
+        doc: &Document,
+        path: &str,
+    ) -> SearchResult<Vec<Translation>> {
+        let key = match doc.get_first(self.translation_key_field).and_then(|f| f.as_text()) {
+            Some(key) => key,
+            None => return Ok(Vec::new()),
+        };
+
+        let query = TermQuery::new(
+            Term::from_field_text(self.translation_key_field, key),
+            IndexRecordOption::Basic,
+        );
+        let mut translations = Vec::new();
+        for (_score, address) in searcher.search(&query, &TopDocs::with_limit(MAX_TRANSLATIONS))? {
+            let other = searcher.doc(address)?;
+            let other_path = self.doc_path(&other);
+            if other_path == path {
+                continue;
+            }
+            translations.push(Translation {
+                lang: self.doc_language(&other).code().to_string(),
+                path: other_path.to_string(),
+                title: self.doc_title(&other).to_string(),
+            });
+        }
+        translations.sort_by(|a, b| a.lang.cmp(&b.lang).then_with(|| a.path.cmp(&b.path)));
+        Ok(translations)
+    }
+
+    /// 从 TOML 文件加载同义词表，替换当前的同义词表
+    /// 
+    /// # Arguments
+    /// * `path` - 同义词文件路径
+    /// 
+    /// # Returns
+    /// * `SearchResult<usize>` - 规则中的源词数量或错误
+    pub fn load_synonyms<P: AsRef<Path>>(&self, path: P) -> SearchResult<usize> {
+        let path = path.as_ref();
+        let modified = std::fs::metadata(path)?.modified().ok();
+        let map = SynonymMap::load(path)?;
+        let count = map.len();
+
+        *self.synonyms.write().unwrap() = SynonymSource {
+            path: Some(path.to_path_buf()),
+            modified,
+            map: Arc::new(map),
+        };
+        // 同义词影响查询的解析，缓存的结果不再有效
+        self.query_cache.clear();
+        Ok(count)
+    }
+
+    /// 加载 `indexer popularity` 生成的热度文件，返回有热度的文章数
+    ///
+    /// 查询点击率立即生效；文章热度在建立索引时写入，需要重建索引才会更新。
+    pub fn load_popularity<P: AsRef<Path>>(&self, path: P) -> SearchResult<usize> {
+        let popularity = Popularity::load(path.as_ref())?;
+        let count = popularity.posts.len();
+        self.set_popularity(popularity);
+        Ok(count)
+    }
+
+    pub fn set_popularity(&self, popularity: Popularity) {
+        *self.popularity.write().unwrap() = Arc::new(popularity);
+        self.query_cache.clear();
+    }
+
+    /// 同义词文件修改后重新加载
+    /// 
+    /// 解析失败时保留原有的同义词表。
+    /// 
+    /// # Returns
+    /// * `SearchResult<bool>` - 是否重新加载了同义词表
+    pub fn reload_synonyms(&self) -> SearchResult<bool> {
+        let (path, loaded_at) = {
+            let source = self.synonyms.read().unwrap();
+            match &source.path {
+                Some(path) => (path.clone(), source.modified),
+                None => return Ok(false),
+            }
+        };
+
+        let modified = std::fs::metadata(&path)?.modified().ok();
+        if modified.is_some() && modified == loaded_at {
+            return Ok(false);
+        }
+
+        self.load_synonyms(&path)?;
+        Ok(true)
+    }
+
+    /// 按同义词表扩展查询字符串
+    pub fn expand_query(&self, query_str: &str) -> String {
+        let map = Arc::clone(&self.synonyms.read().unwrap().map);
+        map.expand(query_str)
+    }
+
+    /// 输入即搜索的补全建议
+    /// 
+    /// 建议来自文档标题、标签和标题中的单词，按包含它们的文档数排序。
+    /// 前缀索引在首次调用时构建，并在读取器加载新的索引代次后重建。
+    /// 
+    /// # Arguments
+    /// * `prefix` - 用户已输入的文本
+    /// * `limit` - 最多返回的建议数量
+    /// 
+    /// # Returns
+    /// * `SearchResult<Vec<Suggestion>>` - 补全建议或错误
+    pub fn suggest(&self, prefix: &str, limit: usize) -> SearchResult<Vec<Suggestion>> {
+        if prefix.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let searcher = self.reader.searcher();
+        let suggest_index = self.suggest_index_for(&searcher)?;
+        Ok(suggest_index.lookup(prefix, limit))
+    }
+
+    fn suggest_index_for(&self, searcher: &Searcher) -> SearchResult<Arc<SuggestIndex>> {
+        let generation = searcher.generation().generation_id();
+        self.suggest_index.get_or_build(generation, || {
+            let mut builder = SuggestIndex::builder();
+            for_each_doc(searcher, |doc| {
+                builder.add_document(self.doc_title(doc), &self.doc_tags(doc));
+            })?;
+            Ok(builder.build())
+        })
+    }
+
+    /// 搜索文档，并在结果过少时给出拼写纠错建议
+    /// 
+    /// 原查询没有结果而纠正后的查询有结果时，直接返回纠正后查询的结果
+    /// （`auto_corrected` 为 `true`）；结果较少时只附带建议，结果保持不变。
+    /// 
+    /// # Arguments
+    /// * `query_str` - 搜索查询字符串
+    /// * `language` - 只返回该语言的文档；为 `None` 时不限语言
+    /// 
+    /// # Returns
+    /// * `SearchResult<CorrectedSearch>` - 搜索结果及纠错建议或错误
+    pub fn search_with_suggestion(
+        &self,
+        query_str: &str,
+        language: Option<Language>,
+    ) -> SearchResult<CorrectedSearch> {
+        let mut builder = SearchRequest::builder(query_str).correct_spelling(true);
+        if let Some(language) = language {
+            builder = builder.lang(language);
+        }
+        let response = self.execute(&builder.build())?;
+        Ok(CorrectedSearch {
+            results: response.hits.into_iter().map(SearchDoc::from).collect(),
+            suggestion: response.suggestion,
+        })
+    }
+
+    /// 根据索引文档中出现的词纠正查询的拼写
+    /// 
+    /// 词典基于存储的原文构建，因此英文文档中的词以原形而非词干参与纠错。
+    /// 
+    /// # Returns
+    /// * `SearchResult<Option<String>>` - 纠正后的查询；无需纠正时为 `None`
+    pub fn correct_query(&self, query_str: &str) -> SearchResult<Option<String>> {
+        let searcher = self.reader.searcher();
+        let generation = searcher.generation().generation_id();
+        let dictionary = self.spell_dictionary.get_or_build(generation, || {
+            let mut dictionary = SpellDictionary::default();
+            for_each_doc(&searcher, |doc| {
+                let text = format!("{}\n{}", self.doc_title(doc), self.doc_content(doc));
+                let mut words: Vec<String> = Analyzer::Cjk
+                    .analyze(&text)
+                    .into_iter()
+                    .map(|token| token.text)
+                    .collect();
+                words.sort_unstable();
+                words.dedup();
+                for word in words {
+                    dictionary.add_term(&word, 1);
+                }
+            })?;
+            Ok(dictionary)
+        })?;
+
+        Ok(dictionary.correct_query(query_str))
+    }
+
+    fn language_fields(&self, language: Language) -> LanguageFields {
+        match language {
+            Language::Zh => self.zh_fields,
+            Language::En => self.en_fields,
+        }
+    }
+
+    /// 文档标题（可能存放在任一语言的字段中）
+    fn doc_title<'a>(&self, doc: &'a Document) -> &'a str {
+        doc.get_first(self.zh_fields.title)
+            .or_else(|| doc.get_first(self.en_fields.title))
+            .and_then(|f| f.as_text())
+            .unwrap_or("")
+    }
+
+    fn doc_path<'a>(&self, doc: &'a Document) -> &'a str {
+        doc.get_first(self.path_field)
+            .and_then(|f| f.as_text())
+            .unwrap_or("")
+    }
+
+    fn doc_language(&self, doc: &Document) -> Language {
+        doc.get_first(self.lang_field)
+            .and_then(|f| f.as_facet())
+            .and_then(|facet| facet.to_path().first().and_then(|code| Language::from_code(code)))
+            .unwrap_or_default()
+    }
+
+    /// 文档正文（可能存放在任一语言的字段中）
+    fn doc_content<'a>(&self, doc: &'a Document) -> &'a str {
+        doc.get_first(self.zh_fields.content)
+            .or_else(|| doc.get_first(self.en_fields.content))
+            .and_then(|f| f.as_text())
+            .unwrap_or("")
+    }
+
+    fn doc_tags(&self, doc: &Document) -> Vec<String> {
+        doc.get_all(self.tags_field)
+            .filter_map(|f| f.as_facet())
+            .filter_map(|facet| facet.to_path().last().map(|tag| tag.to_string()))
+            .collect()
+    }
+
+    fn doc_date(&self, doc: &Document) -> Option<String> {
+        doc.get_first(self.date_field)
+            .and_then(|f| f.as_i64())
+            .and_then(format_date)
+    }
+
+    /// 清空索引
+    /// 
+    /// 删除索引中的所有文档。这个操作不可撤销。
+    /// 
+    /// # Returns
+    /// * `SearchResult<()>` - 成功或错误
+    pub fn clear(&self) -> SearchResult<()> {
+        let mut writer = self.index.writer(DEFAULT_WRITER_MEMORY)?;
+        writer.delete_all_documents()?;
+        writer.commit()?;
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    /// 引擎和 HTTP 服务共用的指标
+    pub fn metrics(&self) -> &Arc<Metrics> {
+        &self.metrics
+    }
+
+    /// 获取索引中的文档数量
+    /// 
+    /// # Returns
+    /// * `SearchResult<u64>` - 文档数量或错误
+    pub fn doc_count(&self) -> SearchResult<u64> {
+        Ok(self.reader.searcher().num_docs())
+    }
+
+    /// 检查索引是否存在
+    pub fn exists(&self) -> bool {
+        self.index.reader().is_ok()
+    }
+
+    /// 检查索引是否可用
+    ///
+    /// 重新读取磁盘上的 `meta.json`，返回最近一次提交的编号、时间和文档数。
+    pub fn health(&self) -> SearchResult<IndexHealth> {
+        let metas = self.index.load_metas()?;
+        let committed_at = std::fs::metadata(self.index_path.join("meta.json"))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .and_then(|elapsed| format_datetime(elapsed.as_secs() as i64));
+        Ok(IndexHealth {
+            doc_count: metas.segments.iter().map(|segment| u64::from(segment.num_docs())).sum(),
+            segment_count: metas.segments.len(),
+            generation: metas.opstamp,
+            committed_at,
+            schema_version: SCHEMA_VERSION,
+        })
+    }
+
+    /// 获取索引统计信息
+    pub fn stats(&self) -> SearchResult<IndexStats> {
+        let searcher = self.reader.searcher();
+        Ok(IndexStats {
+            doc_count: searcher.num_docs(),
+            field_count: self.schema.fields().count() as u64,
+        })
+    }
+}
+
+#[cfg(feature = "server")]
+/// 执行搜索；给定截止时间时超时返回 `SearchError::Timeout`
+fn collect_within<C: Collector>(
+    searcher: &Searcher,
+    query: &dyn Query,
+    collector: &C,
+    deadline: Option<Instant>,
+) -> SearchResult<C::Fruit> {
+    let Some(deadline) = deadline else {
+        return Ok(searcher.search(query, collector)?);
+    };
+    let deadline = Arc::new(Deadline::new(deadline));
+    let query = DeadlineQuery::new(query.box_clone(), Arc::clone(&deadline));
+    let fruit = searcher.search(&query, collector);
+    // 超时的段可能只返回了部分结果而没有报错
+    if deadline.expired() {
+        return Err(SearchError::Timeout);
+    }
+    Ok(fruit?)
+}
+
+#[cfg(feature = "server")]
+/// 语言分面，例如 `/en`
+fn language_facet(language: Language) -> Facet {
+    Facet::from_path([language.code()])
+}
+
+#[cfg(feature = "server")]
+/// 拆分文件名中的语言后缀
+/// 
+/// `post.en.md` 返回 `(post.md, Some(En))`；没有可识别的语言后缀时路径保持不变。
+fn split_language_suffix(path: &Path) -> (String, Option<Language>) {
+    let stem = path.file_stem().and_then(|s| s.to_str());
+    let suffix = stem
+        .and_then(|stem| stem.rsplit_once('.'))
+        .and_then(|(base, code)| Language::from_code(code).map(|language| (base, language)));
+
+    match suffix {
+        Some((base, language)) => {
+            let mut file_name = base.to_string();
+            if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+                file_name.push('.');
+                file_name.push_str(extension);
+            }
+            (path.with_file_name(file_name).to_string_lossy().to_string(), Some(language))
+        }
+        None => (path.to_string_lossy().to_string(), None),
+    }
+}
+
+#[cfg(feature = "server")]
+/// 解析 front matter 中的日期（`"2025-01-18"` 或 TOML 日期时间），返回 Unix 时间戳（秒）
+fn parse_date(value: &toml::Value) -> Option<i64> {
+    let text = match value {
+        toml::Value::String(text) => text.clone(),
+        toml::Value::Datetime(datetime) => datetime.to_string(),
+        _ => return None,
+    };
+    let mut parts = text.get(..10)?.split('-');
+    let year: i32 = parts.next()?.parse().ok()?;
+    let month: u8 = parts.next()?.parse().ok()?;
+    let day: u8 = parts.next()?.parse().ok()?;
+    let month = tantivy::time::Month::try_from(month).ok()?;
+    let date = tantivy::time::Date::from_calendar_date(year, month, day).ok()?;
+    Some(date.midnight().assume_utc().unix_timestamp())
+}
+
+#[cfg(feature = "server")]
+/// 把 Unix 时间戳格式化为 `YYYY-MM-DD`
+fn format_date(timestamp: i64) -> Option<String> {
+    tantivy::time::OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .map(|datetime| datetime.date().to_string())
+}
+
+#[cfg(feature = "server")]
+/// 把 Unix 时间戳格式化为 RFC 3339 的 UTC 时间，例如 `2025-01-18T08:30:00Z`
+pub(crate) fn format_datetime(timestamp: i64) -> Option<String> {
+    let datetime = tantivy::time::OffsetDateTime::from_unix_timestamp(timestamp).ok()?;
+    Some(format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        datetime.date(),
+        datetime.hour(),
+        datetime.minute(),
+        datetime.second()
+    ))
+}
+
+#[cfg(feature = "server")]
+/// 遍历当前读取器中所有未删除的文档
+fn for_each_doc<F>(searcher: &Searcher, mut f: F) -> SearchResult<()>
+where
+    F: FnMut(&Document),
+{
+    for segment_reader in searcher.segment_readers() {
+        let store_reader = segment_reader.get_store_reader(0)?;
+        for doc_id in segment_reader.doc_ids_alive() {
+            f(&store_reader.get(doc_id)?);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "server")]
+/// 截取正文开头作为摘要
+fn excerpt(content: &str) -> String {
+    let words: Vec<&str> = content.split_whitespace().collect();
+    let mut excerpt = if words.len() > DEFAULT_EXCERPT_LENGTH {
+        words[..DEFAULT_EXCERPT_LENGTH].join(" ") + "..."
+    } else {
+        words.join(" ")
+    };
+    if excerpt.chars().count() > MAX_EXCERPT_CHARS {
+        excerpt = excerpt.chars().take(MAX_EXCERPT_CHARS).collect::<String>() + "...";
+    }
+    excerpt
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexStats {
+    pub doc_count: u64,
+    pub field_count: u64,
+}
+
+/// [`SearchEngine::sync_documents`] 的结果，路径与索引中的路径相同
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SyncSummary {
+    /// 写入或替换的文档
+    pub upserted: Vec<String>,
+    /// 从索引中删除的文档
+    pub deleted: Vec<String>,
+    /// 无法读取或解析、保留原来版本的文件
+    pub skipped: Vec<SkippedDocument>,
+}
+
+impl SyncSummary {
+    /// 索引是否有修改
+    pub fn changed(&self) -> bool {
+        !self.upserted.is_empty() || !self.deleted.is_empty()
+    }
+}
+
+/// 同步时跳过的文件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkippedDocument {
+    pub path: String,
+    pub error: String,
+}
+
+/// 索引健康状况，见 [`SearchEngine::health`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexHealth {
+    /// 最近一次提交中未删除的文档数
+    pub doc_count: u64,
+    pub segment_count: usize,
+    /// 最近一次提交的编号（tantivy opstamp），每次提交递增
+    pub generation: u64,
+    /// 最近一次提交的时间（UTC），无法读取时为 `None`
+    pub committed_at: Option<String>,
+    /// 见 [`SCHEMA_VERSION`]
+    pub schema_version: u32,
+}
+
+/// SHA-256 摘要的前 8 个字节，十六进制表示
+#[cfg(feature = "server")]
+pub(crate) fn short_digest(hasher: Sha256) -> String {
+    hex::encode(&hasher.finalize()[..8])
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// 清理重试次数
+    const CLEANUP_RETRY_COUNT: u32 = 3;
+    /// 清理重试间隔（毫秒）
+    const CLEANUP_RETRY_INTERVAL: u64 = 100;
+
+    mod setup {
+        use super::*;
+
+        pub(crate) fn setup_test_dir(name: &str) -> SearchResult<PathBuf> {
+            let test_dir = PathBuf::from("target").join("test_indexes").join(name);
+            if test_dir.exists() {
+                cleanup_test_dir(&test_dir)?;
+            }
+            fs::create_dir_all(&test_dir)?;
+            Ok(test_dir)
+        }
+
+        pub(crate) fn cleanup_test_dir(dir: &Path) -> SearchResult<()> {
+            if dir.exists() {
+                for _ in 0..CLEANUP_RETRY_COUNT {
+                    match fs::remove_dir_all(dir) {
+                        Ok(_) => break,
+                        Err(e) if e.kind() == std::io::ErrorKind::DirectoryNotEmpty => {
+                            std::thread::sleep(std::time::Duration::from_millis(CLEANUP_RETRY_INTERVAL));
+                            continue;
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// 把 `tests/fixtures/corpus` 下的文章全部写入索引
+        pub(crate) fn index_fixture_corpus(engine: &SearchEngine) -> SearchResult<()> {
+            let mut entries: Vec<_> = fs::read_dir(Path::new("tests").join("fixtures").join("corpus"))?
+                .collect::<Result<_, _>>()?;
+            entries.sort_by_key(|entry| entry.file_name());
+            for entry in entries {
+                let content = fs::read_to_string(entry.path())?;
+                engine.index_document(&content, Path::new(&entry.file_name()))?;
+            }
+            Ok(())
+        }
+    }
+
+    mod indexing {
+        use super::*;
+        use super::setup::*;
+
+        #[test]
+        fn test_document_indexing() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_doc")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            
+            let content = r#"+++
+            title = "Test Document"
+            [taxonomies]
+            tags = ["test", "rust"]
+            +++
+            This is a test document."#;
+            
+            engine.index_document(content, &PathBuf::from("test.md"))?;
+            
+            let results = engine.search("test")?;
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].title, "Test Document");
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_invalid_front_matter() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_invalid")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            
+            let content = r#"+++
+            invalid toml content
+            +++
+            Test content"#;
+            
+            assert!(engine.index_document(content, &PathBuf::from("invalid.md")).is_err());
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+    }
+
+    mod searching {
+        use super::*;
+        use super::setup::*;
+
+        #[test]
+        fn test_search_with_empty_query() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_empty")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            
+            let results = engine.search("")?;
+            assert!(results.is_empty());
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_search_with_limit() -> SearchResult<()> {
             let test_dir = setup_test_dir("index_limit")?;
             let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
             
-            for i in 1..=15 {
-                let content = format!(
-                    r#"+++
-                    title = "Test Document {}"
-                    [taxonomies]
-                    tags = ["test"]
-                    +++
-                    This is test document {}."#,
-                    i, i
-                );
-                engine.index_document(&content, &PathBuf::from(format!("test{}.md", i)))?;
+            for i in 1..=15 {
+                let content = format!(
+                    r#"+++
+                    title = "Test Document {}"
+                    [taxonomies]
+                    tags = ["test"]
+                    +++
+                    This is test document {}."#,
+                    i, i
+                );
+                engine.index_document(&content, &PathBuf::from(format!("test{}.md", i)))?;
+            }
+            
+            let results = engine.search("test")?;
+            assert_eq!(results.len(), request::DEFAULT_PAGE_SIZE);
+            
+            drop(engine);
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_search_by_content() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_content")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            
+            let content = r#"+++
+            title = "Test Document"
+            [taxonomies]
+            tags = ["test"]
+            +++
+            This document contains unique_keyword."#;
+            
+            engine.index_document(content, &PathBuf::from("test.md"))?;
+            
+            let results = engine.search("unique_keyword")?;
+            assert_eq!(results.len(), 1);
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+    }
+
+    mod languages {
+        use super::*;
+        use super::setup::*;
+
+        #[test]
+        fn test_english_stop_words_and_stemming() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_lang_en")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            
+            let indexing = r#"+++
+            title = "Indexing Pipelines"
+            +++
+            We build indexing pipelines for blog posts."#;
+            let cooking = r#"+++
+            title = "How to Cook"
+            +++
+            How to make tea and how to boil water."#;
+            engine.index_document(indexing, &PathBuf::from("indexing.md"))?;
+            engine.index_document(cooking, &PathBuf::from("cooking.md"))?;
+            
+            let results = engine.search("how to index")?;
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].title, "Indexing Pipelines");
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_cjk_segmentation_and_lang_override() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_lang_zh")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            
+            let chinese = r#"+++
+            title = "测试文章"
+            +++
+            这是一篇测试文章，用来测试搜索功能。"#;
+            // 显式声明为中文时不做词干提取
+            let forced = r#"+++
+            title = "Indexing"
+            lang = "zh"
+            +++
+            Indexing notes."#;
+            engine.index_document(chinese, &PathBuf::from("zh.md"))?;
+            engine.index_document(forced, &PathBuf::from("forced.md"))?;
+            
+            assert_eq!(engine.search("搜索功能")?.len(), 1);
+            assert_eq!(engine.search("测试")?[0].title, "测试文章");
+            assert!(engine.search("index")?.is_empty());
+            assert_eq!(engine.search("indexing")?.len(), 1);
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+    }
+
+    mod translations {
+        use super::*;
+        use super::setup::*;
+
+        fn index_translations(engine: &SearchEngine) -> SearchResult<()> {
+            let chinese = r#"+++
+            title = "错误处理"
+            +++
+            Rust 错误处理指南，介绍 Result 类型。"#;
+            let english = r#"+++
+            title = "Error Handling"
+            +++
+            A guide to error handling with the Result type."#;
+            engine.index_document(chinese, &PathBuf::from("blog/errors/index.md"))?;
+            engine.index_document(english, &PathBuf::from("blog/errors/index.en.md"))?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_language_filter() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_lang_filter")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_translations(&engine)?;
+            
+            assert_eq!(engine.search("result")?.len(), 2);
+            let results = engine.search_in_language("result", Some(Language::En))?;
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].lang, "en");
+            assert_eq!(results[0].path, "blog/errors/index.en.md");
+            assert_eq!(engine.search_in_language("result", Some(Language::Zh))?[0].lang, "zh");
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_translations_linked() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_translations")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_translations(&engine)?;
+            
+            let results = engine.search("错误处理")?;
+            assert_eq!(results.len(), 1);
+            assert_eq!(
+                results[0].translations,
+                vec![Translation {
+                    lang: "en".to_string(),
+                    path: "blog/errors/index.en.md".to_string(),
+                    title: "Error Handling".to_string(),
+                }]
+            );
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_split_language_suffix() {
+            assert_eq!(
+                split_language_suffix(Path::new("blog/post.en.md")),
+                ("blog/post.md".to_string(), Some(Language::En))
+            );
+            assert_eq!(
+                split_language_suffix(Path::new("blog/v1.2.md")),
+                ("blog/v1.2.md".to_string(), None)
+            );
+        }
+    }
+
+    mod suggesting {
+        use super::*;
+        use super::setup::*;
+
+        #[test]
+        fn test_suggest_titles_and_tags() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_suggest")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            
+            let content = r#"+++
+            title = "Tantivy Tutorial"
+            [taxonomies]
+            tags = ["tantivy", "rust"]
+            +++
+            Building a search engine."#;
+            
+            engine.index_document(content, &PathBuf::from("tantivy.md"))?;
+            
+            let suggestions = engine.suggest("tan", DEFAULT_SUGGEST_LIMIT)?;
+            assert_eq!(suggestions[0].text, "tantivy");
+            assert_eq!(suggestions[0].kind, SuggestionKind::Tag);
+            assert!(suggestions.iter().any(|s| s.text == "Tantivy Tutorial"));
+            assert!(engine.suggest("", DEFAULT_SUGGEST_LIMIT)?.is_empty());
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_suggest_refreshes_after_indexing() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_suggest_refresh")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            
+            assert!(engine.suggest("rust", DEFAULT_SUGGEST_LIMIT)?.is_empty());
+            
+            let content = r#"+++
+            title = "Rust Notes"
+            +++
+            Notes."#;
+            engine.index_document(content, &PathBuf::from("rust.md"))?;
+            
+            let suggestions = engine.suggest("rust", DEFAULT_SUGGEST_LIMIT)?;
+            assert_eq!(suggestions.len(), 2);
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+    }
+
+    mod spelling {
+        use super::*;
+        use super::setup::*;
+
+        fn index_sample(engine: &SearchEngine) -> SearchResult<()> {
+            for (i, title) in ["Tantivy Internals", "Tantivy Tokenizers"].iter().enumerate() {
+                let content = format!(
+                    r#"+++
+                    title = "{}"
+                    +++
+                    Notes about the tantivy search library."#,
+                    title
+                );
+                engine.index_document(&content, &PathBuf::from(format!("tantivy{}.md", i)))?;
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn test_zero_results_auto_corrected() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_spelling_auto")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_sample(&engine)?;
+            
+            let outcome = engine.search_with_suggestion("tantvy", None)?;
+            assert_eq!(outcome.results.len(), 2);
+            assert_eq!(
+                outcome.suggestion,
+                Some(SpellingSuggestion {
+                    suggestion: "tantivy".to_string(),
+                    auto_corrected: true,
+                })
+            );
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_no_suggestion_for_known_or_cjk_terms() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_spelling_none")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_sample(&engine)?;
+            
+            assert!(engine.search_with_suggestion("tantivy", None)?.suggestion.is_none());
+            
+            let outcome = engine.search_with_suggestion("搜索引擎", None)?;
+            assert!(outcome.results.is_empty());
+            assert!(outcome.suggestion.is_none());
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+    }
+
+    mod synonyms {
+        use super::*;
+        use super::setup::*;
+
+        #[test]
+        fn test_search_expands_synonyms() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_synonyms")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            
+            let content = r#"+++
+            title = "Kubernetes Operators"
+            +++
+            Writing a controller."#;
+            engine.index_document(content, &PathBuf::from("kubernetes.md"))?;
+            assert!(engine.search("k8s")?.is_empty());
+            
+            let synonyms_path = test_dir.join("synonyms.toml");
+            fs::write(&synonyms_path, r#"two_way = [["k8s", "kubernetes"]]"#)?;
+            assert_eq!(engine.load_synonyms(&synonyms_path)?, 2);
+            assert_eq!(engine.expand_query("k8s operators"), "(k8s OR kubernetes) operators");
+            assert_eq!(engine.search("k8s")?.len(), 1);
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_reload_synonyms() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_synonyms_reload")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            assert!(!engine.reload_synonyms()?);
+            
+            let synonyms_path = test_dir.join("synonyms.toml");
+            fs::write(&synonyms_path, "[one_way]\njs = [\"javascript\"]")?;
+            engine.load_synonyms(&synonyms_path)?;
+            assert!(!engine.reload_synonyms()?);
+            
+            fs::write(&synonyms_path, "[one_way]\njs = [\"javascript\", \"ecmascript\"]")?;
+            let modified = SystemTime::now() + std::time::Duration::from_secs(1);
+            fs::File::options().write(true).open(&synonyms_path)?.set_modified(modified)?;
+            assert!(engine.reload_synonyms()?);
+            assert_eq!(engine.expand_query("js"), "(js OR javascript OR ecmascript)");
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+    }
+
+    mod phrases {
+        use super::*;
+        use super::setup::*;
+
+        fn paths(results: &[SearchDoc]) -> Vec<&str> {
+            results.iter().map(|doc| doc.path.as_str()).collect()
+        }
+
+        #[test]
+        fn test_phrase_and_slop() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_phrases")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            
+            assert_eq!(paths(&engine.search("\"error handling\"")?), ["error-handling.md"]);
+            
+            let results = engine.search("\"error handling\"~3")?;
+            let mut near = paths(&results);
+            near.sort();
+            assert_eq!(near, ["careful-errors.md", "error-handling.md"]);
+            
+            // 中文短语按二元组的位置匹配，“处理…错误”不会命中“错误处理”
+            assert_eq!(paths(&engine.search("\"错误处理\"")?), ["cuowu-chuli.md"]);
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_proximity_boost() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_proximity")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            
+            // 三篇都包含两个词；signals.md 的词频最高，但两个词相距太远，排在最后
+            let results = engine.search("error handling")?;
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[2].path, "signals.md");
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+    }
+
+    mod requests {
+        use super::*;
+        use super::setup::*;
+
+        #[test]
+        fn test_paging_filters_and_facets() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_requests")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            
+            let request = SearchRequest::builder("error")
+                .offset(1)
+                .limit(1)
+                .facet(FacetField::Tags)
+                .facet(FacetField::Lang)
+                .build();
+            let response = engine.execute(&request)?;
+            assert_eq!(response.total, 3);
+            assert_eq!(response.hits.len(), 1);
+            assert!(response.hits[0].score > 0.0);
+            assert_eq!(
+                response.facets["tags"],
+                [
+                    FacetCount { value: "rust".to_string(), count: 2 },
+                    FacetCount { value: "unix".to_string(), count: 1 },
+                ]
+            );
+            assert_eq!(response.facets["lang"], [FacetCount { value: "en".to_string(), count: 3 }]);
+            
+            let request = SearchRequest::builder("error").tag("unix").build();
+            let response = engine.execute(&request)?;
+            assert_eq!(response.total, 1);
+            assert_eq!(response.hits[0].path, "signals.md");
+            assert_eq!(response.hits[0].tags.as_deref(), Some(&["unix".to_string()][..]));
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_sort_fields_and_highlight() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_requests_sort")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            
+            let request = SearchRequest::builder("error")
+                .sort(SortOrder::Newest)
+                .fields([HitField::Date])
+                .highlight(true)
+                .build();
+            let response = engine.execute(&request)?;
+            let dates: Vec<_> = response.hits.iter().map(|hit| hit.date.as_deref()).collect();
+            assert_eq!(dates, [Some("2025-01-02"), Some("2024-03-01"), Some("2023-05-10")]);
+            assert!(response.hits.iter().all(|hit| hit.title.is_none() && hit.translations.is_none()));
+            assert!(response.hits[0].highlight.as_deref().unwrap().contains("<b>error</b>"));
+            
+            let request = SearchRequest::builder("error").sort(SortOrder::Oldest).build();
+            assert_eq!(engine.execute(&request)?.hits[0].path, "careful-errors.md");
+            
+            let request = SearchRequest::builder("error").min_score(f32::MAX).build();
+            let response = engine.execute(&request)?;
+            assert_eq!(response.total, 3);
+            assert!(response.hits.is_empty());
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_explain() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_explain")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            
+            let request = SearchRequest::builder("error handling").explain(true).build();
+            let response = engine.execute(&request)?;
+            for hit in &response.hits {
+                let explanation = hit.explanation.as_ref().unwrap();
+                let value = explanation["value"].as_f64().unwrap() as f32;
+                assert!((value - hit.score).abs() < 1e-4);
             }
             
-            let results = engine.search("test")?;
-            assert_eq!(results.len(), DEFAULT_SEARCH_LIMIT);
+            let explanation = engine.explain("error handling", "error-handling.md")?.unwrap();
+            assert!(explanation.value() > 0.0);
+            assert!(explanation.to_pretty_json().contains("error"));
+            assert!(engine.explain("错误处理", "error-handling.md")?.is_none());
+            assert!(matches!(
+                engine.explain("error", "missing.md"),
+                Err(SearchError::DocumentNotFound(_))
+            ));
             
-            drop(engine);
             cleanup_test_dir(&test_dir)?;
             Ok(())
         }
 
         #[test]
-        fn test_search_by_content() -> SearchResult<()> {
-            let test_dir = setup_test_dir("index_content")?;
+        fn test_popular_sort() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_requests_popular")?;
+            let request = |sort: SortOrder| SearchRequest::builder("error").sort(sort).build();
+            let ranked = |response: SearchResponse| -> Vec<(String, Score)> {
+                response.hits.into_iter().map(|hit| (hit.path, hit.score)).collect()
+            };
+            let paths = |hits: &[(String, Score)]| -> Vec<String> {
+                hits.iter().map(|(path, _)| path.clone()).collect()
+            };
+
+            // 没有点击数据时与按相关度排序相同
+            let engine = SearchEngine::new(test_dir.join("plain").to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            let relevance = ranked(engine.execute(&request(SortOrder::Relevance))?);
+            assert!(relevance.len() >= 2);
+            assert_eq!(ranked(engine.execute(&request(SortOrder::Popular))?), relevance);
+
+            // 查询点击率立即生效，不影响按相关度排序
+            let (least, least_score) = relevance.last().cloned().unwrap();
+            let mut popularity = Popularity::default();
+            popularity.queries.insert("error".to_string(), BTreeMap::from([(least.clone(), 1.0)]));
+            engine.set_popularity(popularity);
+            assert_eq!(paths(&ranked(engine.execute(&request(SortOrder::Popular))?))[0], least);
+            assert_eq!(paths(&ranked(engine.execute(&request(SortOrder::Relevance))?)), paths(&relevance));
+
+            // 文章热度在建立索引时写入快速字段
+            let engine = SearchEngine::new(test_dir.join("popular").to_str().unwrap())?;
+            engine.set_popularity(Popularity {
+                posts: BTreeMap::from([(least.clone(), 1.0)]),
+                ..Popularity::default()
+            });
+            index_fixture_corpus(&engine)?;
+            let popular = ranked(engine.execute(&request(SortOrder::Popular))?);
+            let score = popular.iter().find(|(path, _)| *path == least).map(|(_, score)| *score);
+            assert!(score.is_some_and(|score| (score - least_score * (1.0 + POPULARITY_WEIGHT)).abs() < 1e-4));
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+    }
+
+    mod related {
+        use super::*;
+        use super::setup::*;
+
+        #[test]
+        fn test_related_posts() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_related")?;
             let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
             
-            let content = r#"+++
-            title = "Test Document"
+            let related = engine.related("error-handling.md", 2)?;
+            assert_eq!(related.len(), 2);
+            assert_eq!(related[0].path, "careful-errors.md");
+            assert!(related.iter().all(|hit| hit.path != "error-handling.md"));
+            assert!(related[0].title.is_some() && related[0].excerpt.is_none());
+            
+            // 中文文章通过共同的二元组关联，标签相同的英文文章也会入选
+            let related = engine.related("cuowu-chuli.md", DEFAULT_RELATED_LIMIT)?;
+            let paths: Vec<_> = related.iter().map(|hit| hit.path.as_str()).collect();
+            assert!(paths.contains(&"rizhi.md"));
+            assert!(paths.contains(&"error-handling.md"));
+            assert!(!paths.contains(&"cuowu-chuli.md"));
+            
+            assert!(matches!(
+                engine.related("missing.md", 3),
+                Err(SearchError::DocumentNotFound(_))
+            ));
+            assert_eq!(engine.document_paths()?.len(), 5);
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_related_excludes_translations() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_related_translations")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            
+            let english = r#"+++
+            title = "Async Rust"
             [taxonomies]
-            tags = ["test"]
+            tags = ["rust"]
             +++
-            This document contains unique_keyword."#;
+            Futures, executors and async runtimes in Rust."#;
+            let chinese = r#"+++
+            title = "Rust 异步编程"
+            [taxonomies]
+            tags = ["rust"]
+            +++
+            介绍 Rust 中的 Future、执行器和异步运行时。"#;
+            let other = r#"+++
+            title = "Tokio Runtimes"
+            [taxonomies]
+            tags = ["rust"]
+            +++
+            Choosing async runtimes and executors for Rust services."#;
+            engine.index_document(english, &PathBuf::from("async.en.md"))?;
+            engine.index_document(chinese, &PathBuf::from("async.md"))?;
+            engine.index_document(other, &PathBuf::from("tokio.md"))?;
             
-            engine.index_document(content, &PathBuf::from("test.md"))?;
+            // 中文译文带有相同的标签，但不应出现在相关文章中
+            let related = engine.related("async.en.md", DEFAULT_RELATED_LIMIT)?;
+            let paths: Vec<_> = related.iter().map(|hit| hit.path.as_str()).collect();
+            assert_eq!(paths, ["tokio.md"]);
             
-            let results = engine.search("unique_keyword")?;
-            assert_eq!(results.len(), 1);
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+    }
+
+    mod export {
+        use super::*;
+        use super::setup::*;
+
+        #[test]
+        fn test_export_artifact() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_export")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            
+            let artifact = engine.export_artifact()?;
+            assert_eq!(artifact.version, artifact::ARTIFACT_VERSION);
+            assert_eq!(artifact.docs.len(), 5);
+            assert_eq!(artifact.docs[0].path, "careful-errors.md");
+            assert_eq!(artifact.docs[0].date.as_deref(), Some("2023-05-10"));
+            // 英文词项经过词干提取，中文词项为二元组
+            assert_eq!(artifact.postings_of(Language::En, "handl").map(<[_]>::len), Some(3));
+            assert_eq!(artifact.postings_of(Language::Zh, "错误").map(<[_]>::len), Some(2));
+            assert!(artifact.postings_of(Language::Zh, "handl").is_none());
+            
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_static_search_matches_engine() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_static_search")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            let static_search = StaticSearch::new(engine.export_artifact()?)?;
+
+            // 单词查询不受邻近加分影响，两端的排序应当一致
+            for query in ["error", "handling", "错误", "日志", "rust"] {
+                let request = SearchRequest::new(query);
+                let expected: Vec<String> =
+                    engine.execute(&request)?.hits.into_iter().map(|hit| hit.path).collect();
+                let actual: Vec<String> =
+                    static_search.search(&request).hits.into_iter().map(|hit| hit.path).collect();
+                assert_eq!(actual, expected, "查询: {}", query);
+            }
+
+            // 静态索引不包含译文列表，得分也只是近似值
+            let request = SearchRequest::builder("error").sort(SortOrder::Newest).tag("rust").build();
+            let normalize = |hits: Vec<SearchHit>| -> Vec<SearchHit> {
+                hits.into_iter()
+                    .map(|hit| SearchHit { score: 0.0, translations: None, ..hit })
+                    .collect()
+            };
+            assert_eq!(
+                normalize(static_search.search(&request).hits),
+                normalize(engine.execute(&request)?.hits)
+            );
             
             cleanup_test_dir(&test_dir)?;
             Ok(())
         }
     }
 
+    mod http_api {
+        use super::*;
+        use super::setup::*;
+
+        #[test]
+        fn test_shared_http_layer() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_http_api")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            let service = http::SearchService::new(engine, ServiceConfig::default());
+
+            let ctx = http::RequestContext::from_headers(None, None);
+            let response = service.search(&ctx, Some("q=error&page=1&size=2&facets=tags"));
+            assert_eq!(response.status, 200);
+            let body: serde_json::Value = serde_json::from_str(&response.body)?;
+            assert_eq!(body["query"], "error");
+            assert_eq!(body["size"], 2);
+            assert_eq!(body["hits"].as_array().map(Vec::len), Some(2));
+            assert!(body["facets"]["tags"].is_array());
+
+            // 查询语法错误、参数错误、参数超出范围和文档不存在分别映射为 400、400、400 和 404
+            let error_of = |response: http::ApiResponse| -> SearchResult<(u16, serde_json::Value)> {
+                Ok((response.status, serde_json::from_str(&response.body)?))
+            };
+            let (status, body) = error_of(service.search(&ctx, Some("q=nosuchfield:rust")))?;
+            assert_eq!((status, body["code"].as_str()), (400, Some("query_syntax")));
+            assert!(body["details"].is_string());
+            let (status, body) = error_of(service.search(&ctx, Some("q=rust&page=x")))?;
+            assert_eq!((status, body["code"].as_str()), (400, Some("invalid_parameter")));
+            assert_eq!(body["request_id"].as_str(), Some(ctx.request_id.as_str()));
+            let (status, body) = error_of(service.search(&ctx, Some("q=rust&size=500")))?;
+            assert_eq!((status, body["code"].as_str()), (400, Some("parameter_out_of_range")));
+            assert_eq!(body["details"], "1 <= size <= 50");
+            let english = http::RequestContext::from_headers(Some("en-GB,en;q=0.8"), None);
+            let (status, body) = error_of(service.related(&english, Some("path=missing.md")))?;
+            assert_eq!((status, body["code"].as_str()), (404, Some("document_not_found")));
+            assert_eq!(body["message"], "Document not found: missing.md");
+
+            let body: serde_json::Value = serde_json::from_str(&service.suggest(&ctx, Some("q=ca")).body)?;
+            assert_eq!(body["query"], "ca");
+            assert!(body["suggestions"].is_array());
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_health_checks() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_health")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            let service = http::SearchService::new(engine, ServiceConfig::default());
+            let ctx = http::RequestContext::from_headers(Some("en"), None);
+
+            let live = http::live(&ctx);
+            assert_eq!((live.status, live.body.as_str()), (200, r#"{"status":"ok"}"#));
+
+            // 空索引不就绪
+            let response = service.ready(&ctx);
+            assert_eq!(response.status, 503);
+            let body: serde_json::Value = serde_json::from_str(&response.body)?;
+            assert_eq!((body["code"].as_str(), body["details"].as_str()), (Some("index_unavailable"), Some("index is empty")));
+
+            index_fixture_corpus(service.engine())?;
+            let response = service.ready(&ctx);
+            assert_eq!(response.status, 200);
+            let body: serde_json::Value = serde_json::from_str(&response.body)?;
+            assert_eq!(body["status"], "ready");
+            assert_eq!(body["index"]["doc_count"].as_u64(), Some(service.engine().doc_count()?));
+            assert_eq!(body["index"]["schema_version"], SCHEMA_VERSION);
+            assert!(body["index"]["generation"].as_u64() > Some(0));
+            assert!(body["index"]["committed_at"].as_str().is_some_and(|at| at.ends_with('Z')));
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_engine_metrics() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_metrics")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            engine.search("error")?;
+            engine.search("zzzqqq")?;
+            assert!(engine.search("nosuchfield:rust").is_err());
+
+            let text = engine.metrics().render();
+            let value = |name: &str| {
+                text.lines()
+                    .find_map(|line| line.strip_prefix(name)?.strip_prefix(' ')?.parse::<f64>().ok())
+            };
+            assert_eq!(value("blog_search_index_documents"), Some(engine.doc_count()? as f64));
+            assert_eq!(value("blog_search_queries_total"), Some(2.0));
+            assert_eq!(value("blog_search_zero_result_queries_total"), Some(1.0));
+            assert_eq!(value("blog_search_query_parse_errors_total"), Some(1.0));
+            assert_eq!(value("blog_search_indexing_duration_seconds_count"), Some(engine.doc_count()? as f64));
+            assert!(value("blog_search_searcher_reloads_total") > Some(1.0));
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_query_cache() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_query_cache")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            let service = http::SearchService::new(engine, ServiceConfig::default());
+            let ctx = http::RequestContext::from_headers(None, None);
+            let total = |response: &http::ApiResponse| {
+                serde_json::from_str::<serde_json::Value>(&response.body).unwrap()["total"].as_u64()
+            };
+
+            let miss = service.search(&ctx, Some("q=error&tags=rust,web"));
+            assert_eq!(miss.header(http::CACHE_STATUS_HEADER), Some("MISS"));
+            // 归一化之后相同的请求命中缓存
+            let hit = service.search(&ctx, Some("q=%EF%BD%85rror&tags=web,rust"));
+            assert_eq!(hit.header(http::CACHE_STATUS_HEADER), Some("HIT"));
+            assert_eq!(total(&hit), total(&miss));
+            let bypass_ctx = http::RequestContext::from_headers(None, None).with_cache_bypass(Some("1"));
+            let bypass = service.search(&bypass_ctx, Some("q=error&tags=rust,web"));
+            assert_eq!(bypass.header(http::CACHE_STATUS_HEADER), Some("BYPASS"));
+            // 错误响应不带缓存状态
+            assert_eq!(service.search(&ctx, Some("q=error&size=0")).header(http::CACHE_STATUS_HEADER), None);
+
+            // 提交新的文档后读取器加载新代次，缓存失效
+            let before = total(&service.search(&ctx, Some("q=error")));
+            service.engine().index_document(
+                "+++\ntitle = \"Another error\"\n+++\nerror handling again",
+                Path::new("content/blog/another-error.md"),
+            )?;
+            assert!(service.engine().query_cache().is_empty());
+            let after = service.search(&ctx, Some("q=error"));
+            assert_eq!(after.header(http::CACHE_STATUS_HEADER), Some("MISS"));
+            assert_eq!(total(&after), before.map(|total| total + 1));
+
+            let text = service.engine().metrics().render();
+            assert!(text.contains("blog_search_cache_hits_total 1"));
+            assert!(text.contains("blog_search_cache_misses_total 3"));
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_conditional_requests() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_conditional")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            let service = http::SearchService::new(engine, ServiceConfig::default());
+            let ctx = http::RequestContext::from_headers(None, None);
+
+            let response = service.search(&ctx, Some("q=error"));
+            let etag = response.header(http::ETAG_HEADER).unwrap().to_string();
+            assert_eq!(response.header(http::CACHE_CONTROL_HEADER), Some("public, max-age=0, s-maxage=60"));
+            let suggest = service.suggest(&ctx, Some("q=err"));
+            assert_eq!(suggest.header(http::CACHE_CONTROL_HEADER), response.header(http::CACHE_CONTROL_HEADER));
+            // 错误响应不允许缓存
+            let error = service.search(&ctx, None);
+            assert_eq!(error.header(http::ETAG_HEADER), None);
+            assert_eq!(error.header(http::CACHE_CONTROL_HEADER), Some("no-store"));
+
+            let revalidate = ctx.clone().with_if_none_match(Some(&etag));
+            let not_modified = service.search(&revalidate, Some("q=error"));
+            assert_eq!(not_modified.status, 304);
+            assert!(not_modified.body.is_empty());
+            assert_eq!(not_modified.header(http::ETAG_HEADER), Some(etag.as_str()));
+            assert_eq!(service.search(&revalidate, Some("q=rust")).status, 200);
+            // 先校验参数：无效的请求即使 If-None-Match 为 * 也返回 400
+            let any = ctx.clone().with_if_none_match(Some("*"));
+            assert_eq!(service.search(&any, Some("q=error")).status, 304);
+            assert_eq!(service.search(&any, Some("q=error&page=0")).status, 400);
+            assert_eq!(service.search(&any, None).status, 400);
+            assert_eq!(service.related(&any, Some("limit=3")).status, 400);
+
+            // 索引内容变化后 ETag 随之变化
+            service.engine().index_document(
+                "+++\ntitle = \"Another error\"\n+++\nerror handling again",
+                Path::new("content/blog/another-error.md"),
+            )?;
+            let changed = service.search(&revalidate, Some("q=error"));
+            assert_eq!(changed.status, 200);
+            assert_ne!(changed.header(http::ETAG_HEADER), Some(etag.as_str()));
+
+            // 打开同一份索引的另一个实例得到相同的 ETag
+            let reopened = SearchEngine::new(test_dir.to_str().unwrap())?;
+            let other = http::SearchService::new(reopened, ServiceConfig::default());
+            let same = other.search(&ctx, Some("q=error"));
+            assert_eq!(same.header(http::ETAG_HEADER), changed.header(http::ETAG_HEADER));
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_overload_protection() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_overload")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+
+            // 截止时间已过的搜索立即中止
+            let searcher = engine.reader.searcher();
+            let query = engine.build_query("error", &[])?;
+            let past = Some(Instant::now());
+            assert!(matches!(collect_within(&searcher, query.as_ref(), &Count, past), Err(SearchError::Timeout)));
+            let future = Some(Instant::now() + Duration::from_secs(60));
+            assert!(collect_within(&searcher, query.as_ref(), &Count, future)? > 0);
+            let timeout = http::ApiError::from(SearchError::Timeout);
+            assert_eq!((timeout.status(), timeout.retry_after), (503, Some(1)));
+
+            let config = ServiceConfig {
+                execution: ExecutionConfig { max_concurrent_searches: 1, ..ExecutionConfig::default() },
+                ..ServiceConfig::default()
+            };
+            let service = http::SearchService::new(engine, config);
+            let ctx = http::RequestContext::from_headers(None, None);
+            let slot = service.acquire().unwrap();
+            let busy = service.search(&ctx, Some("q=error"));
+            assert_eq!(busy.status, 503);
+            assert_eq!(busy.header(http::RETRY_AFTER_HEADER), Some("1"));
+            let body: serde_json::Value = serde_json::from_str(&busy.body)?;
+            assert_eq!(body["code"], "overloaded");
+            drop(slot);
+            assert_eq!(service.search(&ctx, Some("q=error")).status, 200);
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_admin_api() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_admin")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            let config = ServiceConfig {
+                content: ContentConfig { dir: Path::new("tests").join("fixtures").join("corpus") },
+                admin: AdminConfig { enabled: true, max_body_bytes: 64, ..AdminConfig::default() },
+                ..ServiceConfig::default()
+            };
+            let service = Arc::new(http::SearchService::new(engine, config));
+            let jobs = Arc::new(http::JobQueue::new(Arc::clone(&service))?);
+            assert!(http::AdminService::new(Arc::clone(&jobs), http::AdminCredentials::default()).is_err());
+            let credentials = http::AdminCredentials::new(Some("token".to_string()), None);
+            let admin = http::AdminService::new(jobs, credentials)?;
+            let ctx = http::RequestContext::from_headers(None, None);
+            let request = |method, path, query, body| http::AdminRequest {
+                method,
+                path,
+                query,
+                authorization: Some("Bearer token"),
+                body,
+                ..http::AdminRequest::default()
+            };
+            // 提交任务并等待结束，返回任务记录
+            let run = |request: http::AdminRequest| -> SearchResult<serde_json::Value> {
+                let response = admin.handle(&ctx, &request);
+                assert_eq!(response.status, 202, "{}", response.body);
+                let location = response.header("Location").unwrap().to_string();
+                for _ in 0..500 {
+                    let poll = http::AdminRequest { method: "GET", path: &location, query: None, ..request };
+                    let job: serde_json::Value = serde_json::from_str(&admin.handle(&ctx, &poll).body)?;
+                    if job["status"] == "succeeded" || job["status"] == "failed" {
+                        return Ok(job);
+                    }
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                panic!("任务未结束: {}", location);
+            };
+
+            let job = run(request("POST", "/admin/reindex", None, b""))?;
+            assert_eq!((job["kind"].as_str(), job["result"]["indexed"].as_u64()), (Some("reindex"), Some(5)));
+            let post = b"+++\ntitle = \"Admin Upload\"\n+++\nuploaded";
+            let job = run(request("PUT", "/admin/documents", Some("path=uploaded.md"), post))?;
+            assert_eq!((job["status"].as_str(), job["path"].as_str()), (Some("succeeded"), Some("uploaded.md")));
+            assert_eq!(service.engine().search("uploaded")?.len(), 1);
+            let job = run(request("PUT", "/admin/documents", Some("path=bad.md"), b"no front matter"))?;
+            assert_eq!(job["status"], "failed");
+            assert!(job["error"]["message"].is_string());
+            let job = run(request("DELETE", "/admin/documents", Some("path=uploaded.md"), b""))?;
+            assert_eq!(job["result"]["deleted"], true);
+            let job = run(request("POST", "/admin/optimize", None, b""))?;
+            assert!(job["result"]["merged_segments"].is_u64());
+            // 统计直接返回结果，不提交任务
+            let stats = admin.handle(&ctx, &request("GET", "/admin/stats", None, b""));
+            assert_eq!(stats.status, 200);
+            let stats: serde_json::Value = serde_json::from_str(&stats.body)?;
+            assert_eq!(stats["stats"]["doc_count"], 5);
+            assert_eq!(stats["index"]["segment_count"], 1);
+
+            let status_of = |request: http::AdminRequest| admin.handle(&ctx, &request).status;
+            let unauthenticated = http::AdminRequest { authorization: None, ..request("POST", "/admin/clear", None, b"") };
+            assert_eq!(status_of(unauthenticated), 401);
+            assert_eq!(status_of(request("PUT", "/admin/documents", Some("path=big.md"), &[b'x'; 65])), 413);
+            assert_eq!(status_of(request("DELETE", "/admin/documents", None, b"")), 400);
+            assert_eq!(status_of(request("GET", "/admin/jobs/nosuchjob", None, b"")), 404);
+            assert_eq!(status_of(request("PATCH", "/admin/reindex", None, b"")), 404);
+            assert_eq!(status_of(request("POST", "/admin/stats", None, b"")), 404);
+            assert_eq!(status_of(http::AdminRequest { authorization: None, ..request("GET", "/admin/stats", None, b"") }), 401);
+            let jobs = admin.handle(&ctx, &request("GET", "/admin/jobs", None, b""));
+            let jobs: serde_json::Value = serde_json::from_str(&jobs.body)?;
+            assert_eq!(jobs["jobs"][0]["kind"], "optimize");
+
+            run(request("POST", "/admin/clear", None, b""))?;
+            assert_eq!(service.engine().doc_count()?, 0);
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_webhook() -> SearchResult<()> {
+            use hmac::Mac;
+
+            let test_dir = setup_test_dir("index_webhook")?;
+            let checkout = test_dir.join("checkout");
+            std::fs::create_dir_all(checkout.join("blog"))?;
+            let engine = SearchEngine::new(test_dir.join("index").to_str().unwrap())?;
+            let old = checkout.join("blog").join("old.md");
+            std::fs::write(&old, "+++\ntitle = \"Old Post\"\n+++\nold")?;
+            engine.reindex(checkout.join("blog"))?;
+            let config = ServiceConfig {
+                webhook: WebhookConfig {
+                    enabled: true,
+                    checkout_dir: checkout.clone(),
+                    pull: false,
+                    ..WebhookConfig::default()
+                },
+                ..ServiceConfig::default()
+            };
+            let service = Arc::new(http::SearchService::new(engine, config));
+            let jobs = Arc::new(http::JobQueue::new(Arc::clone(&service))?);
+            assert!(http::WebhookReceiver::new(Arc::clone(&jobs), Some(String::new())).is_err());
+            let webhook = http::WebhookReceiver::new(Arc::clone(&jobs), Some("secret".to_string()))?;
+            let ctx = http::RequestContext::from_headers(None, None);
+            let sign = |body: &[u8]| {
+                let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(b"secret").unwrap();
+                mac.update(body);
+                format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+            };
+            let deliver = |event, body: &str| {
+                let signature = sign(body.as_bytes());
+                let request = http::WebhookRequest {
+                    event: Some(event),
+                    signature: Some(&signature),
+                    content_type: Some("application/json"),
+                    body: body.as_bytes(),
+                };
+                webhook.handle(&ctx, &request)
+            };
+
+            std::fs::write(checkout.join("blog").join("new.md"), "+++\ntitle = \"Webhook Post\"\n+++\nfresh")?;
+            std::fs::remove_file(&old)?;
+            let push = r#"{"ref":"refs/heads/main","commits":[
+                {"added":["blog/new.md","README.md"],"modified":[],"removed":[]},
+                {"added":[],"modified":["blog/new.md"],"removed":["blog/old.md","../escape.md"]}]}"#;
+            let response = deliver("push", push);
+            assert_eq!(response.status, 202, "{}", response.body);
+            let body: serde_json::Value = serde_json::from_str(&response.body)?;
+            assert_eq!((body["status"].as_str(), body["job"]["kind"].as_str()), (Some("queued"), Some("sync")));
+            let id = body["job"]["id"].as_str().unwrap();
+            let mut job = jobs.job(id).unwrap();
+            for _ in 0..500 {
+                if job.status.is_finished() {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+                job = jobs.job(id).unwrap();
+            }
+            assert_eq!(job.status, http::JobStatus::Succeeded, "{:?}", job.error);
+            let result = job.result.unwrap();
+            assert_eq!((result["upserted"].as_array().unwrap().len(), result["deleted"].as_array().unwrap().len()), (1, 1));
+            assert_eq!(service.engine().search("webhook")?.len(), 1);
+            assert!(service.engine().search("old")?.is_empty());
+
+            let ignored = |response: http::ApiResponse| -> SearchResult<bool> {
+                let body: serde_json::Value = serde_json::from_str(&response.body)?;
+                Ok(response.status == 200 && body["status"] == "ignored")
+            };
+            assert!(ignored(deliver("push", r#"{"ref":"refs/heads/draft","commits":[]}"#))?);
+            assert!(ignored(deliver("push", r#"{"ref":"refs/heads/main","deleted":true}"#))?);
+            assert!(ignored(deliver("push", r#"{"ref":"refs/heads/main","commits":[{"modified":["README.md"]}]}"#))?);
+            assert!(ignored(deliver("issues", "{}"))?);
+            assert_eq!(deliver("ping", "{}").status, 200);
+            assert_eq!(deliver("push", "not json").status, 400);
+            let forged = http::WebhookRequest {
+                event: Some("push"),
+                signature: Some("sha256=00"),
+                body: push.as_bytes(),
+                ..http::WebhookRequest::default()
+            };
+            assert_eq!(webhook.handle(&ctx, &forged).status, 401);
+            assert_eq!(webhook.handle(&ctx, &http::WebhookRequest { signature: None, ..forged }).status, 401);
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_query_log() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_query_log")?;
+            let engine = SearchEngine::new(test_dir.join("index").to_str().unwrap())?;
+            index_fixture_corpus(&engine)?;
+            let config = ServiceConfig {
+                query_log: QueryLogConfig { enabled: true, dir: test_dir.join("log"), retention_days: 7 },
+                ..ServiceConfig::default()
+            };
+            let service = http::SearchService::new(engine, config);
+            let ctx = http::RequestContext::from_headers(None, None);
+
+            assert_eq!(service.search(&ctx, Some("q=Error%20me@example.com")).status, 200);
+            assert_eq!(service.search(&ctx, Some("q=rust&page=0")).status, 400);
+            // 304 的重新验证同样写入日志
+            let revalidate = ctx.clone().with_if_none_match(Some("*"));
+            assert_eq!(service.search(&revalidate, Some("q=Error%20me@example.com")).status, 304);
+            let entries = query_log::read_entries(&test_dir.join("log"), 0)?;
+            assert_eq!(entries.len(), 2);
+            assert_eq!((entries[0].query.as_str(), entries[0].page), ("error <email>", 1));
+            assert_eq!(entries[1].hits, entries[0].hits);
+
+            // 点击写入同一个日志
+            let path = service.engine().document_paths()?.remove(0);
+            let click = |body: String| service.click(&ctx, body.as_bytes());
+            let response = click(format!(r#"{{"query":"Error","path":"{}","position":2}}"#, path));
+            assert_eq!((response.status, response.body.as_str()), (200, r#"{"recorded":true}"#));
+            let entries = query_log::read_entries(&test_dir.join("log"), 0)?;
+            assert_eq!((entries[2].clicked.as_deref(), entries[2].position), (Some(path.as_str()), Some(2)));
+
+            let code = |response: http::ApiResponse| -> SearchResult<(u16, String)> {
+                let body: serde_json::Value = serde_json::from_str(&response.body)?;
+                Ok((response.status, body["code"].as_str().unwrap_or_default().to_string()))
+            };
+            assert_eq!(code(click(r#"{"query":"error","path":"missing.md","position":1}"#.to_string()))?, (404, "document_not_found".to_string()));
+            assert_eq!(code(click(format!(r#"{{"query":"error","path":"{}","position":0}}"#, path)))?, (400, "parameter_out_of_range".to_string()));
+            assert_eq!(code(click("not json".to_string()))?, (400, "invalid_parameter".to_string()));
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+    }
+
     mod maintenance {
         use super::*;
         use super::setup::*;
@@ -450,10 +2857,112 @@ mod tests {
             
             let stats = engine.stats()?;
             assert_eq!(stats.doc_count, 0);
-            assert_eq!(stats.field_count, 4); // title, content, path, tags
+            // title, content, title_en, content_en, path, tags, lang, date, translation_key, popularity
+            assert_eq!(stats.field_count, 10);
             
             cleanup_test_dir(&test_dir)?;
             Ok(())
         }
+
+        #[test]
+        fn test_incremental_updates() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_incremental")?;
+            let engine = SearchEngine::new(test_dir.to_str().unwrap())?;
+            let post = |title: &str| format!("+++\ntitle = \"{}\"\n+++\nBody", title);
+            let path = Path::new("post.md");
+
+            assert!(!engine.upsert_document(&post("First draft"), path)?);
+            assert!(engine.upsert_document(&post("Final version"), path)?);
+            assert_eq!(engine.doc_count()?, 1);
+            assert_eq!(engine.search("final")?.len(), 1);
+            assert!(engine.search("draft")?.is_empty());
+            // 无法解析的新版本不影响已有的文档
+            assert!(engine.upsert_document("no front matter", path).is_err());
+            assert_eq!(engine.doc_count()?, 1);
+
+            assert!(engine.delete_document("post.md")?);
+            assert!(!engine.delete_document("post.md")?);
+            assert_eq!(engine.doc_count()?, 0);
+
+            // 重建替换全部内容，多个段合并为一个
+            engine.upsert_document(&post("Stale"), Path::new("stale.md"))?;
+            let corpus = Path::new("tests").join("fixtures").join("corpus");
+            assert_eq!(engine.reindex(&corpus)?, 5);
+            assert_eq!(engine.doc_count()?, 5);
+            assert!(!engine.contains("stale.md")?);
+            engine.upsert_document(&post("Extra"), Path::new("extra.md"))?;
+            assert!(engine.optimize()? > 1);
+            assert_eq!(engine.health()?.segment_count, 1);
+            assert_eq!(engine.doc_count()?, 6);
+            assert!(engine.reindex(test_dir.join("missing")).is_err());
+            assert_eq!(engine.doc_count()?, 6);
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_sync_documents() -> SearchResult<()> {
+            let test_dir = setup_test_dir("index_sync")?;
+            let engine = SearchEngine::new(test_dir.join("index").to_str().unwrap())?;
+            let posts = test_dir.join("posts");
+            std::fs::create_dir_all(&posts)?;
+            let (kept, removed, broken) = (posts.join("kept.md"), posts.join("removed.md"), posts.join("broken.md"));
+            std::fs::write(&kept, "+++\ntitle = \"Kept\"\n+++\nfirst")?;
+            std::fs::write(&removed, "+++\ntitle = \"Removed\"\n+++\nbody")?;
+            std::fs::write(&broken, "+++\ntitle = \"Broken\"\n+++\nbody")?;
+            assert_eq!(engine.reindex(&posts)?, 3);
+
+            std::fs::write(&kept, "+++\ntitle = \"Kept\"\n+++\nsecond")?;
+            std::fs::remove_file(&removed)?;
+            std::fs::write(&broken, "no front matter")?;
+            let never_indexed = posts.join("never.md");
+            let summary = engine.sync_documents(&[&kept, &removed, &broken, &never_indexed])?;
+            assert_eq!(summary.upserted, vec![kept.to_string_lossy().to_string()]);
+            assert_eq!(summary.deleted, vec![removed.to_string_lossy().to_string()]);
+            assert_eq!(summary.skipped.len(), 1);
+            assert_eq!(summary.skipped[0].path, broken.to_string_lossy());
+            // 无法解析的文件保留原来的版本
+            assert_eq!(engine.doc_count()?, 2);
+            assert_eq!(engine.search("second")?.len(), 1);
+            assert!(engine.search("first")?.is_empty());
+            assert!(!engine.sync_documents(&[&never_indexed])?.changed());
+
+            // 同步整个目录不删除目录之外的文档
+            engine.upsert_document("+++\ntitle = \"Uploaded\"\n+++\nbody", Path::new("uploaded.md"))?;
+            let summary = engine.sync_directory(&posts)?;
+            assert_eq!((summary.upserted.len(), summary.skipped.len()), (1, 1));
+            assert!(engine.contains("uploaded.md")?);
+            assert_eq!(engine.doc_count()?, 3);
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_content_watcher() -> SearchResult<()> {
+            let test_dir = setup_test_dir("watch")?;
+            let posts = test_dir.join("posts");
+            std::fs::create_dir_all(posts.join("nested"))?;
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let _watcher = watch::ContentWatcher::start(&posts, Duration::from_millis(50), move |changes| {
+                sender.send(changes.unwrap()).unwrap();
+            })?;
+
+            // 同一文件的多次写入合并为一批，非 Markdown 文件被忽略
+            let post = posts.join("nested").join("draft.md");
+            std::fs::write(&post, "+++\ntitle = \"Draft\"\n+++\none")?;
+            std::fs::write(&post, "+++\ntitle = \"Draft\"\n+++\ntwo")?;
+            std::fs::write(posts.join("notes.txt"), "ignored")?;
+            let batch = receiver.recv_timeout(Duration::from_secs(10)).expect("没有收到变更");
+            assert_eq!(batch, vec![post.clone()]);
+
+            std::fs::remove_file(&post)?;
+            let batch = receiver.recv_timeout(Duration::from_secs(10)).expect("没有收到删除");
+            assert_eq!(batch, vec![post]);
+
+            cleanup_test_dir(&test_dir)?;
+            Ok(())
+        }
     }
-} 
\ No newline at end of file
+}
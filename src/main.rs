@@ -1,90 +1,272 @@
 use axum::{
-    extract::Query,
-    http::{HeaderName, HeaderValue, Method, StatusCode},
+    extract::{rejection::BytesRejection, ConnectInfo, DefaultBodyLimit, MatchedPath, RawQuery, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode, Uri},
+    middleware::{self, Next},
     response::{IntoResponse, Response},
-    routing::{get, get_service},
+    body::Bytes,
+    routing::{any, get, get_service, post},
     Router,
-    Json,
 };
-use serde::Deserialize;
 use std::{
     net::SocketAddr,
-    time::Duration,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 use tower_http::{
-    cors::CorsLayer,
+    compression::CompressionLayer,
     services::ServeDir,
+    set_header::SetResponseHeaderLayer,
     trace::TraceLayer,
 };
-use tracing::{info, error};
-use serde_json::json;
+use tracing::{error, info, warn};
 
-use blog_search_service::{SearchEngine, SearchDoc};
+use blog_search_service::{
+    http,
+    metrics::METRICS_CONTENT_TYPE,
+    rate_limit::{RateLimiter, FORWARDED_FOR_HEADER},
+    watch::{ContentWatcher, DEFAULT_DEBOUNCE},
+    AnalysisConfig, SearchEngine, SearchError, ServiceConfig,
+};
+
+/// 检查同义词文件是否修改的间隔
+const SYNONYMS_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+/// 监视文章目录的命令行参数
+const WATCH_FLAG: &str = "--watch";
 
-#[derive(Debug, Deserialize)]
-struct SearchQuery {
-    q: String,
-    #[serde(default = "default_page")]
-    page: usize,
-    #[serde(default = "default_size")]
-    size: usize,
+/// 所有请求共享的服务状态
+#[derive(Clone)]
+pub struct AppState {
+    service: Arc<http::SearchService>,
+    /// 配置开启限流时存在
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// 与 Vercel 函数共用的 CORS 策略
+    cors: Arc<http::CorsPolicy>,
+    /// 配置开启管理接口时存在
+    admin: Option<Arc<http::AdminService>>,
+    /// 配置开启推送 webhook 时存在
+    webhook: Option<Arc<http::WebhookReceiver>>,
 }
 
-fn default_page() -> usize {
-    1
+/// 存活检查；`/health` 为兼容旧的负载均衡配置保留，行为相同
+async fn handle_live(headers: HeaderMap) -> Response {
+    into_response(http::live(&request_context(&headers)))
 }
 
-fn default_size() -> usize {
-    10
+/// 就绪检查：索引可读且不为空
+async fn handle_ready(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    blocking(request_context(&headers), move |ctx| state.service.ready(ctx)).await
 }
 
-async fn health_check() -> StatusCode {
-    StatusCode::OK
+/// 导出 Prometheus 指标
+async fn handle_metrics(State(state): State<AppState>) -> Response {
+    (
+        [(header::CONTENT_TYPE, METRICS_CONTENT_TYPE)],
+        state.service.engine().metrics().render(),
+    )
+        .into_response()
 }
 
-#[derive(Debug)]
-pub enum AppError {
-    SearchEngine(blog_search_service::SearchError),
-    InvalidRequest(String),
+/// 管理接口：凭据检查、路由和任务都由共用 HTTP 层处理
+async fn handle_admin(
+    State(state): State<AppState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
+) -> Response {
+    let ctx = request_context(&headers);
+    let Some(admin) = &state.admin else {
+        return into_response(ctx.error(&http::ApiError::not_found(uri.path())));
+    };
+    let body = match read_body(body, state.service.config().admin.max_body_bytes) {
+        Ok(body) => body,
+        Err(err) => return into_response(ctx.error(&err)),
+    };
+    let header_value = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let request = http::AdminRequest {
+        method: method.as_str(),
+        path: uri.path(),
+        query: uri.query(),
+        authorization: header_value(header::AUTHORIZATION.as_str()),
+        timestamp: header_value(http::TIMESTAMP_HEADER),
+        signature: header_value(http::SIGNATURE_HEADER),
+        body: &body,
+    };
+    into_response(admin.handle(&ctx, &request))
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::SearchEngine(err) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("搜索引擎错误: {}", err),
-            ),
-            AppError::InvalidRequest(err) => (
-                StatusCode::BAD_REQUEST,
-                format!("无效的请求: {}", err),
-            ),
-        };
+/// 内容仓库的推送 webhook：签名检查和事件处理由共用 HTTP 层完成
+async fn handle_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
+) -> Response {
+    let ctx = request_context(&headers);
+    let Some(webhook) = &state.webhook else {
+        return into_response(ctx.error(&http::ApiError::not_found("/webhook")));
+    };
+    let body = match read_body(body, http::MAX_WEBHOOK_BODY_BYTES) {
+        Ok(body) => body,
+        Err(err) => return into_response(ctx.error(&err)),
+    };
+    let header_value = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let request = http::WebhookRequest {
+        event: header_value(http::GITHUB_EVENT_HEADER),
+        signature: header_value(http::HUB_SIGNATURE_HEADER),
+        content_type: header_value(header::CONTENT_TYPE.as_str()),
+        body: &body,
+    };
+    into_response(webhook.handle(&ctx, &request))
+}
 
-        let body = Json(json!({
-            "error": error_message,
-        }));
+/// 读取正文；超过 `max_body_bytes` 时返回 413
+fn read_body(body: Result<Bytes, BytesRejection>, max_body_bytes: usize) -> Result<Bytes, http::ApiError> {
+    body.map_err(|rejection| {
+        if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            http::ApiError::payload_too_large(max_body_bytes)
+        } else {
+            http::ApiError::invalid_parameter("body").with_details(rejection.body_text())
+        }
+    })
+}
+
+/// 按路由模板记录请求数和耗时
+async fn track_metrics<B>(State(state): State<AppState>, request: Request<B>, next: Next<B>) -> Response {
+    let route = match request.extensions().get::<MatchedPath>() {
+        Some(path) => path.as_str().to_string(),
+        // 静态文件由嵌套的服务处理，没有路由模板
+        None if request.uri().path().starts_with("/static/") => "/static".to_string(),
+        None => "unmatched".to_string(),
+    };
+    let started = Instant::now();
+    let response = next.run(request).await;
+    state
+        .service
+        .engine()
+        .metrics()
+        .observe_request(&route, response.status().as_u16(), started.elapsed());
+    response
+}
 
-        (status, body).into_response()
+/// 按共用的 CORS 策略处理预检请求并给响应加上 CORS 响应头
+async fn apply_cors<B>(State(state): State<AppState>, request: Request<B>, next: Next<B>) -> Response {
+    let origin = request
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    if request.method() == Method::OPTIONS && request.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD) {
+        return into_response(state.cors.preflight(origin.as_deref()));
     }
+
+    let mut response = next.run(request).await;
+    for (name, value) in state.cors.headers(origin.as_deref()) {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response.headers_mut().append(name, value);
+        }
+    }
+    response
 }
 
-pub fn create_app() -> Router {
-    let cors = CorsLayer::new()
-        .allow_origin([
-            "https://your-blog-domain.com".parse::<HeaderValue>().unwrap(),
-            "http://localhost:3000".parse::<HeaderValue>().unwrap(),
-        ])
-        .allow_methods([Method::GET])
-        .allow_headers([
-            HeaderName::from_static("content-type"),
-            HeaderName::from_static("accept"),
-        ])
-        .max_age(Duration::from_secs(3600));
-
-    Router::new()
+/// 按客户端 IP 限流，只作用于 `/api/` 下的接口；超出时返回 429 和 `Retry-After`
+async fn rate_limit<B>(State(state): State<AppState>, request: Request<B>, next: Next<B>) -> Response {
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip());
+    if let (Some(limiter), Some(peer)) = (&state.rate_limiter, peer) {
+        if request.uri().path().starts_with("/api/") {
+            let forwarded_for = request
+                .headers()
+                .get(FORWARDED_FOR_HEADER)
+                .and_then(|value| value.to_str().ok());
+            let client = limiter.client_ip(peer, forwarded_for);
+            if let Err(wait) = limiter.check(client) {
+                let ctx = request_context(request.headers());
+                let retry_after = wait.as_secs_f64().ceil() as u64;
+                return into_response(ctx.error(&http::ApiError::rate_limited(retry_after)));
+            }
+        }
+    }
+    next.run(request).await
+}
+
+/// 从请求头创建共用 HTTP 层的请求上下文
+fn request_context(headers: &HeaderMap) -> http::RequestContext {
+    let header_value = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    http::RequestContext::from_headers(
+        header_value(header::ACCEPT_LANGUAGE.as_str()),
+        header_value(http::REQUEST_ID_HEADER),
+    )
+    .with_cache_bypass(header_value(http::CACHE_BYPASS_HEADER))
+    .with_if_none_match(header_value(header::IF_NONE_MATCH.as_str()))
+}
+
+/// 把共用 HTTP 层的响应转换为 Axum 响应
+fn into_response(response: http::ApiResponse) -> Response {
+    let status = StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    if response.is_server_error() {
+        error!("请求失败: {} {}", response.body, response.internal.as_deref().unwrap_or(""));
+    }
+    let mut headers = HeaderMap::new();
+    // 204、304 等没有正文的响应不带 Content-Type
+    if !response.body.is_empty() {
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(http::CONTENT_TYPE));
+    }
+    for (name, value) in response.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+            headers.insert(name, value);
+        }
+    }
+    (status, headers, response.body).into_response()
+}
+
+/// 创建路由；`jobs` 为空且开启了管理接口或 webhook 时创建新的任务队列
+pub fn create_app(service: Arc<http::SearchService>, jobs: Option<Arc<http::JobQueue>>) -> Router {
+    let rate_limit_config = &service.config().rate_limit;
+    let rate_limiter = rate_limit_config.enabled.then(|| {
+        Arc::new(RateLimiter::new(rate_limit_config).expect("rate_limit 配置在加载时已校验"))
+    });
+    let cors = Arc::new(http::CorsPolicy::new(&service.config().cors));
+    let admin_config = service.config().admin;
+    let webhook_enabled = service.config().webhook.enabled;
+    // 管理接口、webhook 和文件监视共用一个任务队列，索引同一时间只有一个写入者
+    let jobs = jobs.or_else(|| {
+        (admin_config.enabled || webhook_enabled)
+            .then(|| Arc::new(http::JobQueue::new(Arc::clone(&service)).expect("Failed to start index job worker")))
+    });
+    let admin = jobs.as_ref().filter(|_| admin_config.enabled).map(|jobs| {
+        let admin = http::AdminService::new(Arc::clone(jobs), http::AdminCredentials::from_env());
+        Arc::new(admin.expect("开启管理接口需要设置凭据的环境变量"))
+    });
+    let webhook = jobs.as_ref().filter(|_| webhook_enabled).map(|jobs| {
+        let webhook = http::WebhookReceiver::from_env(Arc::clone(jobs));
+        Arc::new(webhook.expect("开启 webhook 需要设置签名密钥的环境变量"))
+    });
+    let state = AppState {
+        service,
+        rate_limiter,
+        cors,
+        admin,
+        webhook,
+    };
+
+    let mut router = Router::new();
+    if admin_config.enabled {
+        // 超过上限的正文不读入内存，在认证之前拒绝
+        let admin_route = any(handle_admin).layer(DefaultBodyLimit::max(admin_config.max_body_bytes));
+        router = router.route("/admin/*path", admin_route);
+    }
+    if webhook_enabled {
+        let webhook_route = post(handle_webhook).layer(DefaultBodyLimit::max(http::MAX_WEBHOOK_BODY_BYTES));
+        router = router.route("/webhook", webhook_route);
+    }
+    router
         .route("/api/search", get(handle_search))
-        .route("/health", get(health_check))
+        .route("/api/suggest", get(handle_suggest))
+        .route("/api/related", get(handle_related))
+        .route("/api/click", post(handle_click))
+        .route("/health", get(handle_live))
+        .route("/health/live", get(handle_live))
+        .route("/health/ready", get(handle_ready))
+        .route("/metrics", get(handle_metrics))
         .nest_service(
             "/static",
             get_service(ServeDir::new("static"))
@@ -95,8 +277,17 @@ pub fn create_app() -> Router {
                     ).into_response()
                 }),
         )
-        .layer(cors)
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit))
+        .route_layer(middleware::from_fn_with_state(state.clone(), track_metrics))
+        .layer(middleware::from_fn_with_state(state.clone(), apply_cors))
+        // 压缩层不设置 Vary，CDN 需要按 Accept-Encoding 区分缓存的响应
+        .layer(CompressionLayer::new())
+        .layer(SetResponseHeaderLayer::appending(
+            header::VARY,
+            HeaderValue::from_static("accept-encoding"),
+        ))
         .layer(TraceLayer::new_for_http())
+        .with_state(state)
 }
 
 #[tokio::main]
@@ -104,64 +295,141 @@ async fn main() {
     // 初始化日志
     tracing_subscriber::fmt::init();
 
+    // 加载服务配置
+    let config_path = ServiceConfig::path();
+    let config = ServiceConfig::load(&config_path).expect("Failed to load service config");
+    info!(
+        "服务配置: {} {:?} {:?} {:?} {:?} {:?}",
+        config_path.display(),
+        config.limits,
+        config.cache,
+        config.execution,
+        config.admin,
+        config.webhook
+    );
+
     // 确保索引目录存在
     let index_path = "./data/search_index";
     std::fs::create_dir_all(index_path).expect("Failed to create index directory");
 
-    // 初始化搜索引擎并建立索引
+    // 打开已有的索引，保留通过管理接口和 webhook 写入的文档；索引结构变化时从头重建
     info!("初始化搜索引擎...");
-    let engine = SearchEngine::new(index_path).expect("Failed to create search engine");
+    let content_dir = config.content.dir.clone();
+    let has_content = content_dir.exists();
+    let engine = match SearchEngine::new(index_path) {
+        Err(SearchError::SchemaMismatch) => {
+            warn!("索引结构与当前版本不一致，从头重建");
+            SearchEngine::recreate(index_path, AnalysisConfig::default())
+        }
+        result => result,
+    }
+    .expect("Failed to create search engine");
     
-    // 索引测试文章
-    let content_dir = "./content/blog";
-    if std::path::Path::new(content_dir).exists() {
-        info!("索引文章目录: {}", content_dir);
-        for entry in std::fs::read_dir(content_dir).expect("Failed to read content directory") {
-            let entry = entry.expect("Failed to read directory entry");
-            let path = entry.path();
-            if path.is_file() && path.extension().map_or(false, |ext| ext == "md") {
-                let content = std::fs::read_to_string(&path).expect("Failed to read file");
-                engine.index_document(&content, &path).expect("Failed to index document");
-                info!("已索引: {}", path.display());
-            }
+    // 加载点击热度：文章热度在索引时写入，查询点击率在排序时使用
+    if config.popularity.path.exists() {
+        match engine.load_popularity(&config.popularity.path) {
+            Ok(count) => info!("已加载文章热度: {} 篇", count),
+            Err(e) => error!("加载文章热度失败: {}", e),
         }
     }
 
-    let app = create_app();
+    // 索引文章目录
+    if has_content {
+        info!("索引文章目录: {}", content_dir.display());
+        let summary = engine.sync_directory(&content_dir).expect("Failed to index content directory");
+        info!("已索引: {} 篇", summary.upserted.len());
+        for skipped in &summary.skipped {
+            warn!("跳过无法解析的文章 {}: {}", skipped.path, skipped.error);
+        }
+    }
+
+    // 加载同义词表，并定期检查文件修改
+    if config.synonyms.path.exists() {
+        match engine.load_synonyms(&config.synonyms.path) {
+            Ok(count) => info!("已加载同义词表: {} 条规则", count),
+            Err(e) => error!("加载同义词表失败: {}", e),
+        }
+    }
+    let service = Arc::new(http::SearchService::new(engine, config));
+    let watched_service = Arc::clone(&service);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SYNONYMS_RELOAD_INTERVAL);
+        loop {
+            interval.tick().await;
+            match watched_service.engine().reload_synonyms() {
+                Ok(true) => info!("已重新加载同义词表"),
+                Ok(false) => {}
+                Err(e) => error!("重新加载同义词表失败: {}", e),
+            }
+        }
+    });
+
+    // --watch：文章保存后作为同步任务提交，读取器在提交后重新加载
+    let watch = std::env::args().skip(1).any(|arg| arg == WATCH_FLAG);
+    let jobs = watch.then(|| Arc::new(http::JobQueue::new(Arc::clone(&service)).expect("Failed to start index job worker")));
+    let _watcher = jobs.as_ref().map(|jobs| {
+        let jobs = Arc::clone(jobs);
+        let watcher = ContentWatcher::start(&content_dir, DEFAULT_DEBOUNCE, move |changes| match changes {
+            Ok(paths) => {
+                let count = paths.len();
+                match jobs.submit(http::Operation::Sync { paths, pull: None }) {
+                    Ok(job) => info!("文章变更: {} 个文件，同步任务 {}", count, job.id),
+                    Err(e) => error!("提交同步任务失败: {}", e),
+                }
+            }
+            Err(e) => error!("监视文章目录出错: {}", e),
+        });
+        info!("监视文章目录: {}", content_dir.display());
+        watcher.expect("Failed to watch content directory")
+    });
+
+    let app = create_app(service, jobs);
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
     info!("Starting server on {}", addr);
 
     axum::Server::bind(&addr)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .unwrap();
 }
 
 async fn handle_search(
-    Query(params): Query<SearchQuery>,
-) -> Result<Json<Vec<SearchDoc>>, AppError> {
-    info!("收到搜索请求: {:?}", params);
-
-    let search_engine = SearchEngine::new("./data/search_index")
-        .map_err(|e| {
-            error!("搜索引擎初始化失败: {}", e);
-            AppError::SearchEngine(e)
-        })?;
-
-    let mut results = search_engine
-        .search(&params.q)
-        .map_err(|e| {
-            error!("搜索失败: {}", e);
-            AppError::SearchEngine(e)
-        })?;
-
-    info!("搜索结果数量: {}", results.len());
-
-    // 实现分页
-    let start = (params.page - 1) * params.size;
-    let end = start + params.size;
-    results.truncate(end);
-    results = results.into_iter().skip(start).collect();
-
-    Ok(Json(results))
-} 
\ No newline at end of file
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    blocking(request_context(&headers), move |ctx| state.service.search(ctx, query.as_deref())).await
+}
+
+async fn handle_suggest(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    blocking(request_context(&headers), move |ctx| state.service.suggest(ctx, query.as_deref())).await
+}
+
+async fn handle_related(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    blocking(request_context(&headers), move |ctx| state.service.related(ctx, query.as_deref())).await
+}
+
+async fn handle_click(State(state): State<AppState>, headers: HeaderMap, body: Bytes) -> Response {
+    blocking(request_context(&headers), move |ctx| state.service.click(ctx, &body)).await
+}
+
+/// 在阻塞线程池中调用共用 HTTP 层：检索和写入查询日志会阻塞，不能占用异步工作线程；
+/// 并发名额在 `handle` 内获取，直到响应生成后才释放
+async fn blocking<F>(ctx: http::RequestContext, handle: F) -> Response
+where
+    F: FnOnce(&http::RequestContext) -> http::ApiResponse + Send + 'static,
+{
+    let task_ctx = ctx.clone();
+    match tokio::task::spawn_blocking(move || handle(&task_ctx)).await {
+        Ok(response) => into_response(response),
+        Err(e) => into_response(ctx.error(&http::ApiError::internal(http::ErrorCode::InternalError, e.to_string()))),
+    }
+}
@@ -0,0 +1,208 @@
+//! Prometheus 指标
+//!
+//! 每个 [`SearchEngine`](crate::SearchEngine) 拥有一组指标：查询数、零结果查询数、
+//! 查询解析错误、搜索超时、查询耗时、结果缓存命中和未命中次数、索引文档数和段数、
+//! 读取器重新加载次数以及索引耗时。
+//! Axum 服务在同一组指标中记录每个路由的请求数和耗时，并通过 `/metrics`
+//! 以 Prometheus 文本格式导出。所有指标名称带有 `blog_search_` 前缀。
+
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+use std::time::Duration;
+use tantivy::{Searcher, SearcherGeneration, Warmer};
+
+/// 指标名称前缀
+const NAMESPACE: &str = "blog_search";
+/// `/metrics` 响应的内容类型
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+/// 请求和查询耗时的直方图分桶（秒）
+const LATENCY_BUCKETS: &[f64] = &[0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+/// 索引耗时的直方图分桶（秒）
+const INDEXING_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// 搜索服务的指标
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    queries: IntCounter,
+    zero_result_queries: IntCounter,
+    query_parse_errors: IntCounter,
+    search_timeouts: IntCounter,
+    query_duration: Histogram,
+    cache_hits: IntCounter,
+    cache_misses: IntCounter,
+    index_documents: IntGauge,
+    index_segments: IntGauge,
+    searcher_reloads: IntCounter,
+    indexing_duration: Histogram,
+    query_log_errors: IntCounter,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some(NAMESPACE.to_string()), None)
+            .expect("指标前缀是合法的名称");
+        let metrics = Metrics {
+            http_requests: IntCounterVec::new(
+                Opts::new("http_requests_total", "按路由和状态码统计的 HTTP 请求数"),
+                &["route", "status"],
+            )
+            .expect("指标定义是合法的"),
+            http_request_duration: HistogramVec::new(
+                HistogramOpts::new("http_request_duration_seconds", "按路由和状态码统计的请求耗时")
+                    .buckets(LATENCY_BUCKETS.to_vec()),
+                &["route", "status"],
+            )
+            .expect("指标定义是合法的"),
+            queries: IntCounter::new("queries_total", "执行的搜索查询数").expect("指标定义是合法的"),
+            zero_result_queries: IntCounter::new("zero_result_queries_total", "没有任何结果的搜索查询数")
+                .expect("指标定义是合法的"),
+            query_parse_errors: IntCounter::new("query_parse_errors_total", "查询语法错误数")
+                .expect("指标定义是合法的"),
+            search_timeouts: IntCounter::new("search_timeouts_total", "超过时间上限而中止的搜索数")
+                .expect("指标定义是合法的"),
+            query_duration: Histogram::with_opts(
+                HistogramOpts::new("query_duration_seconds", "搜索查询在引擎中的耗时")
+                    .buckets(LATENCY_BUCKETS.to_vec()),
+            )
+            .expect("指标定义是合法的"),
+            cache_hits: IntCounter::new("cache_hits_total", "命中结果缓存的搜索数")
+                .expect("指标定义是合法的"),
+            cache_misses: IntCounter::new("cache_misses_total", "未命中结果缓存的搜索数（跳过缓存的不计入）")
+                .expect("指标定义是合法的"),
+            index_documents: IntGauge::new("index_documents", "当前读取器中的文档数")
+                .expect("指标定义是合法的"),
+            index_segments: IntGauge::new("index_segments", "当前读取器中的段数")
+                .expect("指标定义是合法的"),
+            searcher_reloads: IntCounter::new("searcher_reloads_total", "读取器加载新提交的次数（包括首次加载）")
+                .expect("指标定义是合法的"),
+            indexing_duration: Histogram::with_opts(
+                HistogramOpts::new("indexing_duration_seconds", "索引单篇文档（包括提交）的耗时")
+                    .buckets(INDEXING_BUCKETS.to_vec()),
+            )
+            .expect("指标定义是合法的"),
+            query_log_errors: IntCounter::new("query_log_errors_total", "写入查询日志失败的次数")
+                .expect("指标定义是合法的"),
+            registry,
+        };
+        metrics.register_all();
+        metrics
+    }
+
+    fn register_all(&self) {
+        let collectors: [Box<dyn prometheus::core::Collector>; 14] = [
+            Box::new(self.http_requests.clone()),
+            Box::new(self.http_request_duration.clone()),
+            Box::new(self.queries.clone()),
+            Box::new(self.zero_result_queries.clone()),
+            Box::new(self.query_parse_errors.clone()),
+            Box::new(self.search_timeouts.clone()),
+            Box::new(self.query_duration.clone()),
+            Box::new(self.cache_hits.clone()),
+            Box::new(self.cache_misses.clone()),
+            Box::new(self.index_documents.clone()),
+            Box::new(self.index_segments.clone()),
+            Box::new(self.searcher_reloads.clone()),
+            Box::new(self.indexing_duration.clone()),
+            Box::new(self.query_log_errors.clone()),
+        ];
+        for collector in collectors {
+            self.registry.register(collector).expect("指标名称不重复");
+        }
+    }
+
+    /// 记录一个 HTTP 请求；`route` 应为路由模板而不是实际路径，避免标签取值过多
+    pub fn observe_request(&self, route: &str, status: u16, elapsed: Duration) {
+        let status = status.to_string();
+        let labels = [route, status.as_str()];
+        self.http_requests.with_label_values(&labels).inc();
+        self.http_request_duration
+            .with_label_values(&labels)
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// 记录一次成功的搜索查询
+    pub(crate) fn observe_query(&self, elapsed: Duration, total: usize) {
+        self.queries.inc();
+        if total == 0 {
+            self.zero_result_queries.inc();
+        }
+        self.query_duration.observe(elapsed.as_secs_f64());
+    }
+
+    pub(crate) fn query_parse_error(&self) {
+        self.query_parse_errors.inc();
+    }
+
+    pub(crate) fn search_timeout(&self) {
+        self.search_timeouts.inc();
+    }
+
+    pub(crate) fn cache_hit(&self) {
+        self.cache_hits.inc();
+    }
+
+    pub(crate) fn cache_miss(&self) {
+        self.cache_misses.inc();
+    }
+
+    pub(crate) fn observe_indexing(&self, elapsed: Duration) {
+        self.indexing_duration.observe(elapsed.as_secs_f64());
+    }
+
+    /// 写入查询日志失败；日志不影响请求本身的结果
+    pub(crate) fn query_log_error(&self) {
+        self.query_log_errors.inc();
+    }
+
+    /// 以 Prometheus 文本格式导出所有指标
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        // 文本格式的编码只会在写入失败时出错，写入 Vec 不会失败
+        let _ = TextEncoder::new().encode(&self.registry.gather(), &mut buffer);
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 读取器每次加载新的提交时更新文档数和段数
+impl Warmer for Metrics {
+    fn warm(&self, searcher: &Searcher) -> tantivy::Result<()> {
+        self.searcher_reloads.inc();
+        self.index_documents.set(searcher.num_docs() as i64);
+        self.index_segments.set(searcher.segment_readers().len() as i64);
+        Ok(())
+    }
+
+    fn garbage_collect(&self, _live_generations: &[&SearcherGeneration]) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let metrics = Metrics::new();
+        metrics.observe_request("/api/search", 200, Duration::from_millis(3));
+        metrics.observe_query(Duration::from_millis(1), 0);
+        metrics.observe_query(Duration::from_millis(1), 4);
+        metrics.query_parse_error();
+
+        let text = metrics.render();
+        assert!(text.contains(r#"blog_search_http_requests_total{route="/api/search",status="200"} 1"#));
+        assert!(text.contains("blog_search_http_request_duration_seconds_bucket"));
+        assert!(text.contains("blog_search_queries_total 2"));
+        assert!(text.contains("blog_search_zero_result_queries_total 1"));
+        assert!(text.contains("blog_search_query_parse_errors_total 1"));
+        assert!(text.contains("blog_search_index_documents 0"));
+    }
+}
@@ -0,0 +1,176 @@
+//! 点击反馈与文章热度
+//!
+//! `POST /api/click` 把点击写入查询日志（见 `query_log` 模块），`indexer popularity`
+//! 汇总最近一段时间的点击，生成 `data/popularity.json`：
+//!
+//! ```json
+//! {
+//!     "version": 1,
+//!     "generated_at": 1737189000,
+//!     "days": 30,
+//!     "posts": { "content/blog/rust.md": 1.0 },
+//!     "queries": { "rust async": { "content/blog/rust.md": 0.5 } }
+//! }
+//! ```
+//!
+//! `posts` 是 0 到 1 之间的文章热度，建立索引时写入快速字段 `popularity`；
+//! `queries` 是每个查询下文章的点击率。`sort=popular` 时两者都参与排序。
+
+use crate::query_log::QueryLogEntry;
+use crate::{SearchError, SearchResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// 热度文件的格式版本
+pub const POPULARITY_FORMAT_VERSION: u32 = 1;
+/// 查询与文章的组合至少被点击这么多次才作为该查询的点击信号，避免噪声和单个读者的行为
+const MIN_QUERY_CLICKS: usize = 2;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// 文章热度和查询点击信号
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Popularity {
+    /// 见 [`POPULARITY_FORMAT_VERSION`]
+    pub version: u32,
+    /// 生成时间（Unix 时间戳，秒）
+    pub generated_at: i64,
+    /// 汇总的天数
+    pub days: u32,
+    /// 文章路径到热度（0 到 1）
+    pub posts: BTreeMap<String, f64>,
+    /// 匿名化之后的查询到文章路径的点击率（0 到 1）
+    pub queries: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl Default for Popularity {
+    fn default() -> Self {
+        Popularity {
+            version: POPULARITY_FORMAT_VERSION,
+            generated_at: 0,
+            days: 0,
+            posts: BTreeMap::new(),
+            queries: BTreeMap::new(),
+        }
+    }
+}
+
+impl Popularity {
+    /// 汇总查询日志
+    ///
+    /// 每次点击按时间衰减，每过 `half_life_days` 天权重减半；排在后面的结果被点击
+    /// 说明读者更想要它，权重乘以 `log2(1 + position)`。文章热度为
+    /// `ln(1 + 权重和)` 除以所有文章中的最大值。
+    pub fn aggregate(entries: &[QueryLogEntry], now: i64, days: u32, half_life_days: f64) -> Self {
+        let mut weights: HashMap<&str, f64> = HashMap::new();
+        let mut searches: HashMap<&str, usize> = HashMap::new();
+        let mut clicks: HashMap<(&str, &str), usize> = HashMap::new();
+        for entry in entries {
+            match &entry.clicked {
+                Some(path) => {
+                    let age_days = (now - entry.ts).max(0) as f64 / SECONDS_PER_DAY;
+                    let decay = 0.5f64.powf(age_days / half_life_days);
+                    let position = entry.position.unwrap_or(1).max(1) as f64;
+                    *weights.entry(path).or_default() += decay * (1.0 + position).log2();
+                    *clicks.entry((&entry.query, path)).or_default() += 1;
+                }
+                None if entry.page <= 1 => *searches.entry(&entry.query).or_default() += 1,
+                None => {}
+            }
+        }
+
+        let max = weights.values().map(|w| w.ln_1p()).fold(0.0, f64::max);
+        let posts = weights
+            .into_iter()
+            .map(|(path, weight)| (path.to_string(), if max > 0.0 { weight.ln_1p() / max } else { 0.0 }))
+            .collect();
+
+        let mut queries: BTreeMap<String, BTreeMap<String, f64>> = BTreeMap::new();
+        for ((query, path), count) in clicks {
+            if count < MIN_QUERY_CLICKS {
+                continue;
+            }
+            // 点击记录可能早于日志窗口中的搜索记录，点击率不超过 1
+            let shown = searches.get(query).copied().unwrap_or(0).max(count);
+            queries
+                .entry(query.to_string())
+                .or_default()
+                .insert(path.to_string(), count as f64 / shown as f64);
+        }
+
+        Popularity {
+            version: POPULARITY_FORMAT_VERSION,
+            generated_at: now,
+            days,
+            posts,
+            queries,
+        }
+    }
+
+    /// 从文件加载；版本不一致时返回 `SearchError::InvalidParameter`
+    pub fn load(path: &Path) -> SearchResult<Self> {
+        let popularity: Popularity = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if popularity.version != POPULARITY_FORMAT_VERSION {
+            return Err(SearchError::InvalidParameter(format!("popularity.version={}", popularity.version)));
+        }
+        Ok(popularity)
+    }
+
+    pub fn save(&self, path: &Path) -> SearchResult<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 文章热度；没有点击的文章为 0
+    pub fn post(&self, path: &str) -> f64 {
+        self.posts.get(path).copied().unwrap_or(0.0)
+    }
+
+    /// 查询下各文章的点击率
+    pub fn query_signals(&self, query: &str) -> Option<&BTreeMap<String, f64>> {
+        self.queries.get(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(ts: i64, query: &str) -> QueryLogEntry {
+        QueryLogEntry { ts, query: query.to_string(), hits: 5, took_ms: 1, page: 1, clicked: None, position: None }
+    }
+
+    fn click(ts: i64, query: &str, path: &str, position: usize) -> QueryLogEntry {
+        QueryLogEntry { clicked: Some(path.to_string()), position: Some(position), ..search(ts, query) }
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let now = 100 * 86_400;
+        let entries = vec![
+            search(now, "rust"),
+            search(now, "rust"),
+            search(now, "rust"),
+            search(now, "rust"),
+            click(now, "rust", "a.md", 1),
+            click(now, "rust", "a.md", 1),
+            click(now, "rust", "b.md", 2),
+            // 很久以前的点击几乎没有权重
+            click(now - 90 * 86_400, "go", "c.md", 1),
+        ];
+        let popularity = Popularity::aggregate(&entries, now, 90, 7.0);
+
+        assert_eq!(popularity.post("a.md"), 1.0);
+        assert!(popularity.post("b.md") > 0.5 && popularity.post("b.md") < 1.0);
+        assert!(popularity.post("c.md") < 0.01);
+        assert_eq!(popularity.post("missing.md"), 0.0);
+
+        let signals = popularity.query_signals("rust").unwrap();
+        assert_eq!(signals.get("a.md"), Some(&0.5));
+        assert_eq!(signals.get("b.md"), None);
+        assert!(popularity.query_signals("go").is_none());
+    }
+}
@@ -0,0 +1,113 @@
+//! 查询字符串的预处理
+//!
+//! 同义词扩展和邻近度加权都需要在 `QueryParser` 之前识别查询中的普通词项，
+//! 同时保留短语、字段限定和布尔运算符等语法片段。
+
+use unicode_normalization::UnicodeNormalization;
+
+/// 自动邻近度查询允许的词间距离
+#[cfg(feature = "server")]
+pub(crate) const PROXIMITY_SLOP: u32 = 4;
+/// 自动邻近度查询的权重
+#[cfg(feature = "server")]
+pub(crate) const PROXIMITY_BOOST: f32 = 2.0;
+
+/// 归一化查询：NFKC（全角字母、数字和符号转为半角）、控制字符视为空白、
+/// 合并连续空白并去掉首尾空白
+pub(crate) fn normalize_query(query: &str) -> String {
+    let normalized: String = query
+        .nfkc()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    normalized.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 按空白切分查询，双引号内的短语（包括其后的 `~N`）作为一个片段
+pub(crate) fn split_query(query: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut in_quotes = false;
+
+    for (i, c) in query.char_indices() {
+        match c {
+            '"' => {
+                if start.is_none() {
+                    start = Some(i);
+                }
+                in_quotes = !in_quotes;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if let Some(s) = start.take() {
+                    tokens.push(&query[s..i]);
+                }
+            }
+            _ => {
+                if start.is_none() {
+                    start = Some(i);
+                }
+            }
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(&query[s..]);
+    }
+
+    tokens
+}
+
+/// 是否为普通词项（不是运算符、排除项、短语或字段限定）
+pub(crate) fn is_plain_term(token: &str) -> bool {
+    !matches!(token, "AND" | "OR" | "NOT")
+        && !token.starts_with('-')
+        && !token.contains(|c: char| "\":()[]{}^~*+\\".contains(c))
+}
+
+/// 为多词查询生成邻近度短语，例如 `error handling` -> `"error handling"~4`
+///
+/// 查询中已经有显式短语或普通词项少于两个时返回 `None`。
+#[cfg(feature = "server")]
+pub(crate) fn proximity_phrase(query: &str) -> Option<String> {
+    if query.contains('"') {
+        return None;
+    }
+
+    let terms: Vec<&str> = split_query(query)
+        .into_iter()
+        .filter(|token| is_plain_term(token))
+        .collect();
+    if terms.len() < 2 {
+        return None;
+    }
+
+    Some(format!("\"{}\"~{}", terms.join(" "), PROXIMITY_SLOP))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_query() {
+        assert_eq!(normalize_query("  Ｒｕｓｔ\t\u{0}错误　处理  "), "Rust 错误 处理");
+        assert_eq!(normalize_query("\"error  handling\"~３"), "\"error handling\"~3");
+    }
+
+    #[test]
+    fn test_split_query_keeps_phrases() {
+        assert_eq!(
+            split_query("  \"error  handling\"~3 rust  -go "),
+            ["\"error  handling\"~3", "rust", "-go"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn test_proximity_phrase() {
+        assert_eq!(
+            proximity_phrase("error handling AND rust"),
+            Some("\"error handling rust\"~4".to_string())
+        );
+        assert_eq!(proximity_phrase("error title:rust"), None);
+        assert_eq!(proximity_phrase("\"error handling\" rust"), None);
+    }
+}
@@ -0,0 +1,426 @@
+//! 查询日志与统计报告
+//!
+//! 默认关闭，在 `config/service.toml` 的 `[query_log]` 中开启。每次搜索写入一行
+//! JSON：
+//!
+//! ```json
+//! {"ts":1737189000,"query":"rust 错误处理","hits":12,"took_ms":3,"page":1}
+//! ```
+//!
+//! 为保护读者隐私，日志不记录 IP、请求头和请求 ID；时间精确到分钟；查询经过
+//! 归一化并转为小写，其中的邮箱地址和较长的数字串分别替换为 `<email>` 和
+//! `<number>`。点击记录的 `clicked` 为文章路径，`position` 为它在结果中的位置。
+//!
+//! 日志按天写入 `queries-YYYY-MM-DD.jsonl`，超过保留天数的文件在切换到新的
+//! 一天时删除。
+
+use crate::config::QueryLogConfig;
+use crate::query::normalize_query;
+use crate::SearchResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+/// 日志文件名的前缀和后缀
+const FILE_PREFIX: &str = "queries-";
+const FILE_SUFFIX: &str = ".jsonl";
+/// 至少包含这么多位数字的词替换为 `<number>`（电话号码、订单号等）
+const MIN_REDACTED_DIGITS: usize = 6;
+/// 趋势词至少在最近的时间段中出现这么多次
+const MIN_TRENDING_COUNT: usize = 2;
+
+/// 一条日志
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryLogEntry {
+    /// Unix 时间戳（秒），向下取整到分钟
+    pub ts: i64,
+    /// 匿名化之后的查询
+    pub query: String,
+    pub hits: usize,
+    pub took_ms: u64,
+    pub page: usize,
+    /// 点击记录中被点击的文章路径；搜索记录中不出现
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clicked: Option<String>,
+    /// 点击记录中文章在结果中的位置，从 1 开始
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+}
+
+impl QueryLogEntry {
+    fn is_click(&self) -> bool {
+        self.clicked.is_some()
+    }
+}
+
+/// 按天轮转的查询日志
+pub struct QueryLog {
+    dir: PathBuf,
+    retention_days: u32,
+    /// 当前写入的文件及其日期
+    current: Mutex<Option<(String, File)>>,
+}
+
+impl QueryLog {
+    pub fn new(config: &QueryLogConfig) -> Self {
+        QueryLog {
+            dir: config.dir.clone(),
+            retention_days: config.retention_days,
+            current: Mutex::new(None),
+        }
+    }
+
+    /// 配置开启时创建
+    pub fn from_config(config: &QueryLogConfig) -> Option<Self> {
+        config.enabled.then(|| QueryLog::new(config))
+    }
+
+    /// 记录一次搜索
+    pub fn record_search(&self, query: &str, hits: usize, took_ms: u64, page: usize) -> SearchResult<()> {
+        self.append(QueryLogEntry {
+            ts: now(),
+            query: anonymize_query(query),
+            hits,
+            took_ms,
+            page,
+            clicked: None,
+            position: None,
+        })
+    }
+
+    /// 记录一次结果点击，`position` 从 1 开始
+    pub fn record_click(&self, query: &str, path: &str, position: usize) -> SearchResult<()> {
+        self.append(QueryLogEntry {
+            ts: now(),
+            query: anonymize_query(query),
+            hits: 0,
+            took_ms: 0,
+            page: 0,
+            clicked: Some(path.to_string()),
+            position: Some(position),
+        })
+    }
+
+    /// 写入一条日志；日期变化时切换文件并删除过期文件
+    pub fn append(&self, mut entry: QueryLogEntry) -> SearchResult<()> {
+        entry.ts -= entry.ts.rem_euclid(60);
+        let day = format_day(entry.ts);
+        let mut current = self.current.lock().unwrap();
+        if current.as_ref().map(|(open_day, _)| open_day) != Some(&day) {
+            fs::create_dir_all(&self.dir)?;
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.dir.join(log_file_name(&day)))?;
+            *current = Some((day, file));
+            self.prune(entry.ts)?;
+        }
+        let (_, file) = current.as_mut().expect("已打开当前日期的文件");
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// 删除早于保留期限的文件
+    fn prune(&self, now: i64) -> SearchResult<()> {
+        let oldest = format_day(now - i64::from(self.retention_days) * SECONDS_PER_DAY);
+        for (day, path) in log_files(&self.dir)? {
+            if day < oldest {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 读取 `since` 之后的日志；无法解析的行被跳过
+pub fn read_entries(dir: &Path, since: i64) -> SearchResult<Vec<QueryLogEntry>> {
+    let first_day = format_day(since);
+    let mut entries = Vec::new();
+    for (day, path) in log_files(dir)? {
+        if day < first_day {
+            continue;
+        }
+        for line in BufReader::new(File::open(path)?).lines() {
+            if let Ok(entry) = serde_json::from_str::<QueryLogEntry>(&line?) {
+                if entry.ts >= since {
+                    entries.push(entry);
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// 目录中的日志文件及其日期；目录不存在时为空
+fn log_files(dir: &Path) -> SearchResult<Vec<(String, PathBuf)>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let day = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_SUFFIX))
+            .map(str::to_string);
+        if let Some(day) = day {
+            files.push((day, path));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn log_file_name(day: &str) -> String {
+    format!("{}{}{}", FILE_PREFIX, day, FILE_SUFFIX)
+}
+
+/// 把 Unix 时间戳格式化为 UTC 日期 `YYYY-MM-DD`
+fn format_day(timestamp: i64) -> String {
+    tantivy::time::OffsetDateTime::from_unix_timestamp(timestamp)
+        .map(|datetime| datetime.date().to_string())
+        .unwrap_or_default()
+}
+
+/// 当前的 Unix 时间戳（秒）
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// 归一化并去掉可能识别个人身份的内容
+pub fn anonymize_query(query: &str) -> String {
+    normalize_query(query)
+        .to_lowercase()
+        .split(' ')
+        .map(|word| {
+            if word.contains('@') {
+                "<email>"
+            } else if word.chars().filter(char::is_ascii_digit).count() >= MIN_REDACTED_DIGITS {
+                "<number>"
+            } else {
+                word
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 一个查询的统计
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryStat {
+    pub query: String,
+    /// 搜索次数
+    pub count: usize,
+    /// 平均结果数
+    pub avg_hits: f64,
+    /// 点击次数
+    pub clicks: usize,
+}
+
+/// 词项在最近时间段和之前同样长的时间段中的出现次数
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TermTrend {
+    pub term: String,
+    pub count: usize,
+    pub previous_count: usize,
+}
+
+/// 最近 N 天的查询报告
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryReport {
+    pub days: u32,
+    pub searches: usize,
+    pub zero_result_searches: usize,
+    /// 按搜索次数排序
+    pub top_queries: Vec<QueryStat>,
+    /// 没有结果的查询，按搜索次数排序
+    pub zero_result_queries: Vec<QueryStat>,
+    /// 出现次数相对之前同样长的时间段增长最多的词项
+    pub trending_terms: Vec<TermTrend>,
+}
+
+impl QueryReport {
+    /// 读取日志目录生成报告；趋势需要读取之前同样长的时间段
+    pub fn from_dir(dir: &Path, now: i64, days: u32, limit: usize) -> SearchResult<Self> {
+        let window = i64::from(days) * SECONDS_PER_DAY;
+        let entries = read_entries(dir, now - 2 * window)?;
+        Ok(QueryReport::build(&entries, now, days, limit))
+    }
+
+    /// 根据日志生成 `now` 之前 `days` 天的报告，每个列表最多 `limit` 项
+    pub fn build(entries: &[QueryLogEntry], now: i64, days: u32, limit: usize) -> Self {
+        let window = i64::from(days) * SECONDS_PER_DAY;
+        let start = now - window;
+        let (recent, previous): (Vec<&QueryLogEntry>, Vec<&QueryLogEntry>) = entries
+            .iter()
+            .filter(|entry| entry.ts >= start - window && entry.ts <= now)
+            .partition(|entry| entry.ts >= start);
+
+        // 只统计第一页的搜索，翻页不算新的搜索
+        let searches: Vec<&QueryLogEntry> = recent
+            .iter()
+            .copied()
+            .filter(|entry| !entry.is_click() && entry.page <= 1)
+            .collect();
+        let mut stats: HashMap<&str, QueryStat> = HashMap::new();
+        for entry in &searches {
+            let stat = stats.entry(&entry.query).or_insert_with(|| QueryStat {
+                query: entry.query.clone(),
+                count: 0,
+                avg_hits: 0.0,
+                clicks: 0,
+            });
+            stat.count += 1;
+            stat.avg_hits += entry.hits as f64;
+        }
+        for entry in recent.iter().filter(|entry| entry.is_click()) {
+            if let Some(stat) = stats.get_mut(entry.query.as_str()) {
+                stat.clicks += 1;
+            }
+        }
+        let mut stats: Vec<QueryStat> = stats
+            .into_values()
+            .map(|mut stat| {
+                stat.avg_hits /= stat.count as f64;
+                stat
+            })
+            .collect();
+        stats.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.query.cmp(&b.query)));
+
+        let zero_result_queries = stats
+            .iter()
+            .filter(|stat| stat.avg_hits == 0.0)
+            .take(limit)
+            .cloned()
+            .collect();
+
+        QueryReport {
+            days,
+            searches: searches.len(),
+            zero_result_searches: searches.iter().filter(|entry| entry.hits == 0).count(),
+            top_queries: stats.into_iter().take(limit).collect(),
+            zero_result_queries,
+            trending_terms: trending_terms(&recent, &previous, limit),
+        }
+    }
+}
+
+/// 按 (最近次数 + 1) / (之前次数 + 1) 排序的词项
+fn trending_terms(recent: &[&QueryLogEntry], previous: &[&QueryLogEntry], limit: usize) -> Vec<TermTrend> {
+    let count_terms = |entries: &[&QueryLogEntry]| {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for entry in entries.iter().filter(|entry| !entry.is_click() && entry.page <= 1) {
+            for term in entry.query.split(' ') {
+                let term = term.trim_matches(|c: char| !c.is_alphanumeric());
+                if !term.is_empty() {
+                    *counts.entry(term.to_string()).or_default() += 1;
+                }
+            }
+        }
+        counts
+    };
+    let recent = count_terms(recent);
+    let previous = count_terms(previous);
+
+    let mut trends: Vec<TermTrend> = recent
+        .into_iter()
+        .filter(|(_, count)| *count >= MIN_TRENDING_COUNT)
+        .map(|(term, count)| TermTrend {
+            previous_count: previous.get(&term).copied().unwrap_or(0),
+            term,
+            count,
+        })
+        .filter(|trend| trend.count > trend.previous_count)
+        .collect();
+    let growth = |trend: &TermTrend| (trend.count + 1) as f64 / (trend.previous_count + 1) as f64;
+    trends.sort_by(|a, b| {
+        growth(b)
+            .total_cmp(&growth(a))
+            .then(b.count.cmp(&a.count))
+            .then_with(|| a.term.cmp(&b.term))
+    });
+    trends.truncate(limit);
+    trends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(ts: i64, query: &str, hits: usize) -> QueryLogEntry {
+        QueryLogEntry { ts, query: query.to_string(), hits, took_ms: 1, page: 1, clicked: None, position: None }
+    }
+
+    #[test]
+    fn test_anonymize_query() {
+        assert_eq!(anonymize_query("  Ｒust　Error "), "rust error");
+        assert_eq!(anonymize_query("contact me@example.com 13800138000 v1.2"), "contact <email> <number> v1.2");
+    }
+
+    #[test]
+    fn test_rotation_and_retention() -> SearchResult<()> {
+        let dir = PathBuf::from("target").join("test_query_log");
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        let config = QueryLogConfig { enabled: true, dir: dir.clone(), retention_days: 2 };
+        let log = QueryLog::new(&config);
+        let day0 = 1_737_158_400; // 2025-01-18T00:00:00Z
+        log.append(entry(day0 + 125, "rust", 3))?;
+        log.append(entry(day0 + 3600, "go", 0))?;
+        log.append(entry(day0 + SECONDS_PER_DAY, "rust", 2))?;
+
+        let entries = read_entries(&dir, day0)?;
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].ts, day0 + 120);
+        assert!(dir.join("queries-2025-01-19.jsonl").exists());
+
+        log.append(entry(day0 + 3 * SECONDS_PER_DAY, "rust", 1))?;
+        assert!(!dir.join("queries-2025-01-18.jsonl").exists());
+        assert_eq!(read_entries(&dir, 0)?.len(), 2);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_report() {
+        let now = 100 * SECONDS_PER_DAY;
+        let recent = now - 3600;
+        let previous = now - 10 * SECONDS_PER_DAY;
+        let mut entries = vec![
+            entry(recent, "rust async", 4),
+            entry(recent, "rust async", 6),
+            entry(recent, "kubernetes", 0),
+            entry(recent, "rust", 8),
+            entry(previous, "rust", 8),
+            entry(previous, "rust", 8),
+            entry(previous, "rust", 8),
+            entry(now - 20 * SECONDS_PER_DAY, "ignored", 0),
+        ];
+        entries.push(QueryLogEntry { page: 2, ..entry(recent, "rust async", 4) });
+        entries.push(QueryLogEntry { clicked: Some("post.md".to_string()), ..entry(recent, "rust async", 0) });
+
+        let report = QueryReport::build(&entries, now, 7, 10);
+        assert_eq!((report.searches, report.zero_result_searches), (4, 1));
+        assert_eq!(
+            report.top_queries[0],
+            QueryStat { query: "rust async".to_string(), count: 2, avg_hits: 5.0, clicks: 1 }
+        );
+        assert_eq!(report.zero_result_queries.len(), 1);
+        assert_eq!(report.zero_result_queries[0].query, "kubernetes");
+        // rust 在之前的时间段出现 3 次，最近只有 3 次，不算趋势
+        let terms: Vec<&str> = report.trending_terms.iter().map(|t| t.term.as_str()).collect();
+        assert_eq!(terms, vec!["async"]);
+    }
+}
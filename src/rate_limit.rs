@@ -0,0 +1,205 @@
+//! 按客户端 IP 的请求限流
+//!
+//! 每个客户端 IP 一个令牌桶：桶中最多 `burst` 个令牌，每秒补充 `requests_per_second` 个，
+//! 每个请求消耗一个，没有令牌时返回需要等待的时间，由前端返回 429 和 `Retry-After`。
+//!
+//! 服务部署在反向代理之后时，对端地址是代理的地址。只有对端地址属于
+//! `trusted_proxies` 时才读取 `X-Forwarded-For`：从右向左跳过可信代理，
+//! 第一个不可信的地址就是客户端；否则客户端可以伪造该请求头绕过限流。
+
+use crate::config::RateLimitConfig;
+use crate::{SearchError, SearchResult};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 客户端地址的请求头
+pub const FORWARDED_FOR_HEADER: &str = "X-Forwarded-For";
+/// 记录的客户端数超过该值时清理已经补满的令牌桶
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// IP 地址段，例如 `10.0.0.0/8`；不带前缀长度时只包含这一个地址
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                prefix_matches(u32::from(network) as u128, u32::from(ip) as u128, self.prefix, 32)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                prefix_matches(u128::from(network), u128::from(ip), self.prefix, 128)
+            }
+            // IPv4 映射的 IPv6 地址按 IPv4 比较
+            (IpAddr::V4(_), IpAddr::V6(ip)) => ip.to_ipv4_mapped().is_some_and(|ip| self.contains(IpAddr::V4(ip))),
+            (IpAddr::V6(_), IpAddr::V4(_)) => false,
+        }
+    }
+}
+
+fn prefix_matches(network: u128, ip: u128, prefix: u8, bits: u8) -> bool {
+    let shift = bits - prefix;
+    shift == bits || (network >> shift) == (ip >> shift)
+}
+
+impl FromStr for IpNetwork {
+    type Err = SearchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SearchError::InvalidParameter(format!("trusted_proxies={}", s));
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr.trim().parse().map_err(|_| invalid())?;
+        let bits = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.trim().parse::<u8>().ok().filter(|prefix| *prefix <= bits).ok_or_else(invalid)?,
+            None => bits,
+        };
+        Ok(IpNetwork { addr, prefix })
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// 按客户端 IP 的令牌桶限流器
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    trusted_proxies: Vec<IpNetwork>,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    /// 按配置创建；`trusted_proxies` 中有无法解析的地址段时返回错误
+    pub fn new(config: &RateLimitConfig) -> SearchResult<Self> {
+        let trusted_proxies = config
+            .trusted_proxies
+            .iter()
+            .map(|network| network.parse())
+            .collect::<SearchResult<_>>()?;
+        Ok(RateLimiter {
+            rate: config.requests_per_second,
+            burst: config.burst as f64,
+            trusted_proxies,
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|network| network.contains(ip))
+    }
+
+    /// 客户端地址：对端为可信代理时从 `X-Forwarded-For` 中取最右边的不可信地址
+    pub fn client_ip(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+        let mut client = peer;
+        for hop in forwarded_for.unwrap_or_default().rsplit(',') {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) => {
+                    client = ip;
+                    if !self.is_trusted(ip) {
+                        break;
+                    }
+                }
+                // 无法解析的地址不可信，停在上一个地址
+                Err(_) => break,
+            }
+        }
+        client
+    }
+
+    /// 为客户端消耗一个令牌；没有令牌时返回需要等待的时间
+    pub fn check(&self, client: IpAddr) -> Result<(), Duration> {
+        self.check_at(client, Instant::now())
+    }
+
+    fn check_at(&self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            let (rate, burst) = (self.rate, self.burst);
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.saturating_duration_since(bucket.updated).as_secs_f64() * rate < burst
+            });
+        }
+
+        let bucket = buckets.entry(client).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(trusted_proxies: &[&str]) -> RateLimiter {
+        RateLimiter::new(&RateLimitConfig {
+            enabled: true,
+            requests_per_second: 2.0,
+            burst: 3,
+            trusted_proxies: trusted_proxies.iter().map(|s| s.to_string()).collect(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = limiter(&[]);
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check_at(client, now).is_ok());
+        }
+        assert_eq!(limiter.check_at(client, now), Err(Duration::from_millis(500)));
+        // 其他客户端有自己的令牌桶
+        assert!(limiter.check_at("203.0.113.8".parse().unwrap(), now).is_ok());
+        // 每秒补充两个令牌
+        assert!(limiter.check_at(client, now + Duration::from_millis(500)).is_ok());
+        assert!(limiter.check_at(client, now + Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let limiter = limiter(&["10.0.0.0/8", "::1"]);
+        let proxy: IpAddr = "10.1.2.3".parse().unwrap();
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+
+        // 不可信的对端不能通过请求头伪造地址
+        assert_eq!(limiter.client_ip(client, Some("198.51.100.1")), client);
+        assert_eq!(limiter.client_ip(proxy, Some("198.51.100.1, 203.0.113.7, 10.0.0.9")), client);
+        assert_eq!(limiter.client_ip("::1".parse().unwrap(), Some("203.0.113.7")), client);
+        assert_eq!(limiter.client_ip(proxy, None), proxy);
+        assert_eq!(limiter.client_ip(proxy, Some("garbage, 10.0.0.9")), "10.0.0.9".parse::<IpAddr>().unwrap());
+
+        let network: IpNetwork = "192.168.0.0/16".parse().unwrap();
+        assert!(network.contains("192.168.10.1".parse().unwrap()));
+        assert!(network.contains("::ffff:192.168.10.1".parse().unwrap()));
+        assert!(!network.contains("192.169.0.1".parse().unwrap()));
+        assert!("0.0.0.0/0".parse::<IpNetwork>().unwrap().contains("8.8.8.8".parse().unwrap()));
+        assert!("10.0.0.0/33".parse::<IpNetwork>().is_err());
+        assert!("localhost".parse::<IpNetwork>().is_err());
+    }
+}
@@ -0,0 +1,421 @@
+//! 结构化的搜索请求与响应
+//!
+//! ```no_run
+//! use blog_search_service::{SearchEngine, SearchRequest, SortOrder};
+//!
+//! # fn main() -> blog_search_service::SearchResult<()> {
+//! let engine = SearchEngine::new("./data/search_index")?;
+//! let request = SearchRequest::builder("error handling")
+//!     .tag("rust")
+//!     .sort(SortOrder::Newest)
+//!     .offset(10)
+//!     .limit(10)
+//!     .highlight(true)
+//!     .build();
+//! let response = engine.execute(&request)?;
+//! println!("{} / {}", response.hits.len(), response.total);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::analysis::Language;
+use crate::spelling::SpellingSuggestion;
+use crate::{SearchDoc, SearchError, Translation};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// 默认的每页结果数
+pub const DEFAULT_PAGE_SIZE: usize = 10;
+/// 高亮片段的默认最大字符数
+pub const DEFAULT_HIGHLIGHT_CHARS: usize = 150;
+
+/// 过滤条件，多个条件之间为“与”关系
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Filter {
+    /// 包含该标签
+    Tag(String),
+    /// 指定语言
+    Lang(Language),
+}
+
+/// 结果排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// 按相关度
+    #[default]
+    Relevance,
+    /// 按日期从新到旧，没有日期的文章排在最后
+    Newest,
+    /// 按日期从旧到新，没有日期的文章排在最后
+    Oldest,
+    /// 按相关度，并结合读者点击得到的文章热度和查询点击率
+    Popular,
+}
+
+impl FromStr for SortOrder {
+    type Err = SearchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "relevance" => Ok(SortOrder::Relevance),
+            "newest" => Ok(SortOrder::Newest),
+            "oldest" => Ok(SortOrder::Oldest),
+            "popular" => Ok(SortOrder::Popular),
+            _ => Err(SearchError::InvalidParameter(format!("sort={}", s))),
+        }
+    }
+}
+
+/// 可统计分面的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FacetField {
+    /// 标签
+    Tags,
+    /// 语言
+    Lang,
+}
+
+impl FacetField {
+    /// 分面名称，即响应中 `facets` 的键
+    pub fn name(self) -> &'static str {
+        match self {
+            FacetField::Tags => "tags",
+            FacetField::Lang => "lang",
+        }
+    }
+}
+
+impl FromStr for FacetField {
+    type Err = SearchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tags" => Ok(FacetField::Tags),
+            "lang" => Ok(FacetField::Lang),
+            _ => Err(SearchError::InvalidParameter(format!("facets={}", s))),
+        }
+    }
+}
+
+/// 结果中可选返回的字段，`path` 和 `score` 总是返回
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HitField {
+    Title,
+    Excerpt,
+    Tags,
+    Lang,
+    Date,
+    Translations,
+}
+
+impl HitField {
+    /// 全部可选字段
+    pub const ALL: [HitField; 6] = [
+        HitField::Title,
+        HitField::Excerpt,
+        HitField::Tags,
+        HitField::Lang,
+        HitField::Date,
+        HitField::Translations,
+    ];
+}
+
+impl FromStr for HitField {
+    type Err = SearchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(HitField::Title),
+            "excerpt" => Ok(HitField::Excerpt),
+            "tags" => Ok(HitField::Tags),
+            "lang" => Ok(HitField::Lang),
+            "date" => Ok(HitField::Date),
+            "translations" => Ok(HitField::Translations),
+            _ => Err(SearchError::InvalidParameter(format!("fields={}", s))),
+        }
+    }
+}
+
+/// 解析逗号分隔的取值列表，忽略空项
+pub fn parse_list<T: FromStr>(value: &str) -> Result<Vec<T>, T::Err> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(T::from_str)
+        .collect()
+}
+
+/// 高亮选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HighlightOptions {
+    /// 高亮片段的最大字符数
+    pub max_chars: usize,
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        HighlightOptions {
+            max_chars: DEFAULT_HIGHLIGHT_CHARS,
+        }
+    }
+}
+
+/// 搜索请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchRequest {
+    /// 查询字符串
+    pub query: String,
+    /// 过滤条件
+    pub filters: Vec<Filter>,
+    /// 排序方式
+    pub sort: SortOrder,
+    /// 跳过的结果数
+    pub offset: usize,
+    /// 最多返回的结果数；为 0 时只统计总数和分面
+    pub limit: usize,
+    /// 高亮选项；为 `None` 时不生成高亮片段
+    pub highlight: Option<HighlightOptions>,
+    /// 需要统计的分面
+    pub facets: Vec<FacetField>,
+    /// 需要返回的字段；为 `None` 时返回全部字段
+    pub fields: Option<Vec<HitField>>,
+    /// 低于该分数的结果不返回
+    pub min_score: Option<f32>,
+    /// 结果过少时是否给出拼写纠错建议
+    pub correct_spelling: bool,
+    /// 是否为每条结果附带得分的计算过程
+    pub explain: bool,
+}
+
+impl SearchRequest {
+    /// 只包含查询字符串、其余取默认值的请求
+    pub fn new(query: impl Into<String>) -> Self {
+        SearchRequest {
+            query: query.into(),
+            filters: Vec::new(),
+            sort: SortOrder::default(),
+            offset: 0,
+            limit: DEFAULT_PAGE_SIZE,
+            highlight: None,
+            facets: Vec::new(),
+            fields: None,
+            min_score: None,
+            correct_spelling: false,
+            explain: false,
+        }
+    }
+
+    /// 创建请求构建器
+    pub fn builder(query: impl Into<String>) -> SearchRequestBuilder {
+        SearchRequestBuilder {
+            request: SearchRequest::new(query),
+        }
+    }
+
+    /// 是否需要返回某个字段
+    pub fn wants(&self, field: HitField) -> bool {
+        self.fields.as_ref().is_none_or(|fields| fields.contains(&field))
+    }
+}
+
+/// 搜索请求构建器
+#[derive(Debug, Clone)]
+pub struct SearchRequestBuilder {
+    request: SearchRequest,
+}
+
+impl SearchRequestBuilder {
+    /// 添加过滤条件
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.request.filters.push(filter);
+        self
+    }
+
+    /// 只返回包含该标签的文章
+    pub fn tag(self, tag: impl Into<String>) -> Self {
+        self.filter(Filter::Tag(tag.into()))
+    }
+
+    /// 只返回该语言的文章
+    pub fn lang(self, language: Language) -> Self {
+        self.filter(Filter::Lang(language))
+    }
+
+    /// 设置排序方式
+    pub fn sort(mut self, sort: SortOrder) -> Self {
+        self.request.sort = sort;
+        self
+    }
+
+    /// 设置跳过的结果数
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.request.offset = offset;
+        self
+    }
+
+    /// 设置最多返回的结果数
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.request.limit = limit;
+        self
+    }
+
+    /// 使用默认选项开启或关闭高亮
+    pub fn highlight(mut self, enabled: bool) -> Self {
+        self.request.highlight = enabled.then(HighlightOptions::default);
+        self
+    }
+
+    /// 使用指定选项开启高亮
+    pub fn highlight_options(mut self, options: HighlightOptions) -> Self {
+        self.request.highlight = Some(options);
+        self
+    }
+
+    /// 添加需要统计的分面
+    pub fn facet(mut self, facet: FacetField) -> Self {
+        if !self.request.facets.contains(&facet) {
+            self.request.facets.push(facet);
+        }
+        self
+    }
+
+    /// 设置需要返回的字段
+    pub fn fields(mut self, fields: impl IntoIterator<Item = HitField>) -> Self {
+        self.request.fields = Some(fields.into_iter().collect());
+        self
+    }
+
+    /// 设置最低分数
+    pub fn min_score(mut self, min_score: f32) -> Self {
+        self.request.min_score = Some(min_score);
+        self
+    }
+
+    /// 结果过少时给出拼写纠错建议
+    pub fn correct_spelling(mut self, enabled: bool) -> Self {
+        self.request.correct_spelling = enabled;
+        self
+    }
+
+    /// 为每条结果附带得分的计算过程
+    pub fn explain(mut self, enabled: bool) -> Self {
+        self.request.explain = enabled;
+        self
+    }
+
+    /// 完成构建
+    pub fn build(self) -> SearchRequest {
+        self.request
+    }
+}
+
+/// 单条搜索结果
+///
+/// 未在 `fields` 中请求的字段为 `None`，序列化时省略。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    /// 相关度分数
+    pub score: f32,
+    /// 文档路径
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excerpt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// 发布日期（`YYYY-MM-DD`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translations: Option<Vec<Translation>>,
+    /// 正文中命中词项的高亮片段（HTML，命中处用 `<b>` 包裹）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<String>,
+    /// tantivy 的得分解释树，仅在请求开启 `explain` 时返回
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<serde_json::Value>,
+}
+
+impl From<SearchHit> for SearchDoc {
+    fn from(hit: SearchHit) -> Self {
+        SearchDoc {
+            title: hit.title.unwrap_or_default(),
+            path: hit.path,
+            excerpt: hit.excerpt.unwrap_or_default(),
+            tags: hit.tags.unwrap_or_default(),
+            lang: hit.lang.unwrap_or_default(),
+            date: hit.date,
+            translations: hit.translations.unwrap_or_default(),
+        }
+    }
+}
+
+/// 分面中的一个取值及其文档数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: u64,
+}
+
+/// 搜索响应
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchResponse {
+    /// 当前页的结果
+    pub hits: Vec<SearchHit>,
+    /// 匹配的文档总数（不受 `min_score` 影响）
+    pub total: usize,
+    /// 分面统计，按文档数降序
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub facets: BTreeMap<String, Vec<FacetCount>>,
+    /// 搜索耗时（毫秒）
+    pub took_ms: u64,
+    /// 拼写纠错建议
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<SpellingSuggestion>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_and_field_selection() {
+        let request = SearchRequest::builder("rust")
+            .tag("web")
+            .lang(Language::En)
+            .offset(20)
+            .limit(5)
+            .facet(FacetField::Tags)
+            .facet(FacetField::Tags)
+            .fields([HitField::Title])
+            .build();
+        assert_eq!(
+            request.filters,
+            [Filter::Tag("web".to_string()), Filter::Lang(Language::En)]
+        );
+        assert_eq!((request.offset, request.limit), (20, 5));
+        assert_eq!(request.facets, [FacetField::Tags]);
+        assert!(request.wants(HitField::Title));
+        assert!(!request.wants(HitField::Excerpt));
+        assert!(SearchRequest::new("rust").wants(HitField::Excerpt));
+    }
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(
+            parse_list::<HitField>("title, date,,").unwrap(),
+            [HitField::Title, HitField::Date]
+        );
+        assert_eq!("newest".parse::<SortOrder>().unwrap(), SortOrder::Newest);
+        assert!(parse_list::<FacetField>("tags,author").is_err());
+    }
+}
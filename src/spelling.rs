@@ -0,0 +1,149 @@
+//! 拼写纠错（“您是不是要找”）
+//!
+//! 词典由索引中的词项及其文档频率构成。只对拉丁字母词项做编辑距离纠错，
+//! 中文等 CJK 词项的编辑距离没有意义，原样保留。
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 参与纠错的最短词长
+const MIN_WORD_CHARS: usize = 3;
+/// 词长不超过该值时只允许一次编辑
+const SHORT_WORD_CHARS: usize = 4;
+
+/// 纠错建议
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpellingSuggestion {
+    /// 纠正后的查询
+    pub suggestion: String,
+    /// 返回的结果是否已经来自纠正后的查询
+    pub auto_corrected: bool,
+}
+
+/// 拼写词典
+#[derive(Default)]
+pub struct SpellDictionary {
+    words: HashMap<String, u64>,
+}
+
+impl SpellDictionary {
+    /// 加入一个词项及其文档频率
+    ///
+    /// 非拉丁字母词项会被忽略；同一词项多次加入时频率累加。
+    pub fn add_term(&mut self, term: &str, doc_freq: u64) {
+        if is_correctable(term) {
+            *self.words.entry(term.to_string()).or_insert(0) += doc_freq;
+        }
+    }
+
+    /// 词典中的词数
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// 词典是否为空
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// 纠正单个词
+    ///
+    /// 词典中已有的词、过短的词和非拉丁字母词返回 `None`。
+    /// 候选词按编辑距离升序、文档频率降序选取。
+    pub fn correct_word(&self, word: &str) -> Option<String> {
+        let word = word.to_lowercase();
+        if !is_correctable(&word) || self.words.contains_key(&word) {
+            return None;
+        }
+
+        let len = word.chars().count();
+        let max_distance = if len <= SHORT_WORD_CHARS { 1 } else { 2 };
+
+        self.words
+            .iter()
+            .filter(|(candidate, _)| candidate.len().abs_diff(len) <= max_distance)
+            .filter_map(|(candidate, freq)| {
+                let distance = strsim::osa_distance(&word, candidate);
+                (distance <= max_distance).then_some((distance, *freq, candidate))
+            })
+            .min_by(|a, b| {
+                a.0.cmp(&b.0)
+                    .then_with(|| b.1.cmp(&a.1))
+                    .then_with(|| a.2.cmp(b.2))
+            })
+            .map(|(_, _, candidate)| candidate.clone())
+    }
+
+    /// 纠正整条查询
+    ///
+    /// 逐个空白分隔的片段纠错，运算符、字段限定、短语等语法片段保持不变。
+    /// 没有任何片段被修改时返回 `None`。
+    pub fn correct_query(&self, query: &str) -> Option<String> {
+        let mut changed = false;
+        let corrected: Vec<String> = query
+            .split_whitespace()
+            .map(|token| {
+                if is_operator(token) {
+                    return token.to_string();
+                }
+                match self.correct_word(token) {
+                    Some(word) => {
+                        changed = true;
+                        word
+                    }
+                    None => token.to_string(),
+                }
+            })
+            .collect();
+
+        changed.then(|| corrected.join(" "))
+    }
+}
+
+/// 只有足够长的纯 ASCII 字母数字词参与纠错
+fn is_correctable(word: &str) -> bool {
+    word.chars().count() >= MIN_WORD_CHARS
+        && word.chars().all(|c| c.is_ascii_alphanumeric())
+        && word.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+fn is_operator(token: &str) -> bool {
+    matches!(token, "AND" | "OR" | "NOT")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_dictionary() -> SpellDictionary {
+        let mut dictionary = SpellDictionary::default();
+        dictionary.add_term("tantivy", 3);
+        dictionary.add_term("rust", 5);
+        dictionary.add_term("trust", 1);
+        dictionary.add_term("search", 4);
+        dictionary.add_term("搜索", 2);
+        dictionary
+    }
+
+    #[test]
+    fn test_correct_word() {
+        let dictionary = sample_dictionary();
+        assert_eq!(dictionary.correct_word("tantvy"), Some("tantivy".to_string()));
+        assert_eq!(dictionary.correct_word("Serach"), Some("search".to_string()));
+        // 距离相同时选择文档频率更高的词
+        assert_eq!(dictionary.correct_word("tust"), Some("rust".to_string()));
+        assert_eq!(dictionary.correct_word("rust"), None);
+        assert_eq!(dictionary.correct_word("qqqqqq"), None);
+    }
+
+    #[test]
+    fn test_correct_query_keeps_cjk_and_operators() {
+        let dictionary = sample_dictionary();
+        assert_eq!(
+            dictionary.correct_query("tantvy AND 搜素"),
+            Some("tantivy AND 搜素".to_string())
+        );
+        assert_eq!(dictionary.correct_query("rust 搜素"), None);
+        assert_eq!(dictionary.len(), 4);
+    }
+}
@@ -0,0 +1,480 @@
+//! 基于静态索引的搜索，供浏览器端（wasm）在没有搜索服务时使用
+//!
+//! 查询使用与服务端相同的分析器和 BM25 公式（k1 = 1.2，b = 0.75），
+//! 因此同一份索引在两端的匹配结果与排序基本一致。受静态索引内容所限，
+//! 有以下差异：
+//!
+//! - 倒排表没有位置信息：引号短语和一个词切分出的多个词项（如中文二元组）
+//!   只要求全部出现，不检查相邻；也没有邻近加分。
+//! - 不支持字段限定、拼写纠错、得分解释和译文列表。
+//! - 高亮在摘要上进行，而不是在全文中选取片段。
+//!
+//! 支持的查询语法：空格分隔的词（任一匹配即可）、`+词`（必须出现）、
+//! `-词`（必须不出现）和 `"短语"`。
+
+use crate::analysis::{Analyzer, Language};
+use crate::artifact::{shard_key, ArtifactDoc, ArtifactShard, SearchArtifact, ARTIFACT_VERSION};
+use crate::query::split_query;
+use crate::request::{
+    FacetCount, FacetField, Filter, HighlightOptions, HitField, SearchHit, SearchRequest,
+    SearchResponse, SortOrder,
+};
+use crate::{SearchError, SearchResult};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// BM25 的词频饱和参数，与 tantivy 相同
+const BM25_K1: f32 = 1.2;
+/// BM25 的长度归一化参数，与 tantivy 相同
+const BM25_B: f32 = 0.75;
+
+/// 查询中的一个子句
+#[derive(Debug, PartialEq)]
+enum Occur {
+    Should,
+    Must,
+    MustNot,
+}
+
+/// 基于静态索引的搜索
+pub struct StaticSearch {
+    artifact: SearchArtifact,
+    analyzers: Vec<(Language, Analyzer)>,
+    /// 已合并的分片键
+    loaded_shards: HashSet<String>,
+}
+
+impl StaticSearch {
+    /// 从清单（或未分片的完整索引）创建
+    ///
+    /// 版本与 [`ARTIFACT_VERSION`] 不一致时返回 `SearchError::UnsupportedArtifact`。
+    pub fn new(artifact: SearchArtifact) -> SearchResult<Self> {
+        if artifact.version != ARTIFACT_VERSION {
+            return Err(SearchError::UnsupportedArtifact(artifact.version));
+        }
+        let analyzers = [Language::Zh, Language::En]
+            .into_iter()
+            .map(|language| (language, Analyzer::for_language(language, &artifact.analysis)))
+            .collect();
+        Ok(StaticSearch {
+            artifact,
+            analyzers,
+            loaded_shards: HashSet::new(),
+        })
+    }
+
+    /// 从 JSON 文本创建
+    pub fn from_json(json: &str) -> SearchResult<Self> {
+        Self::new(serde_json::from_str(json)?)
+    }
+
+    /// 索引中的文档数
+    pub fn doc_count(&self) -> usize {
+        self.artifact.docs.len()
+    }
+
+    /// 执行查询前还需要下载的分片键
+    pub fn missing_shards(&self, query: &str) -> Vec<String> {
+        let shards: HashSet<&str> = self.artifact.shards.iter().map(String::as_str).collect();
+        let mut missing = BTreeSet::new();
+        for (_, text) in parse_clauses(query) {
+            for (_, analyzer) in &self.analyzers {
+                for token in analyzer.analyze(&text) {
+                    let key = shard_key(&token.text);
+                    if shards.contains(key.as_str()) && !self.loaded_shards.contains(&key) {
+                        missing.insert(key);
+                    }
+                }
+            }
+        }
+        missing.into_iter().collect()
+    }
+
+    /// 合并下载的分片
+    pub fn add_shard(&mut self, key: &str, shard: ArtifactShard) -> SearchResult<()> {
+        if shard.version != self.artifact.version {
+            return Err(SearchError::UnsupportedArtifact(shard.version));
+        }
+        self.artifact.merge_shard(shard);
+        self.loaded_shards.insert(key.to_string());
+        Ok(())
+    }
+
+    /// 执行搜索请求；缺失的分片中的词项视为不存在
+    pub fn search(&self, request: &SearchRequest) -> SearchResponse {
+        let clauses = parse_clauses(&request.query);
+        if clauses.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+            return SearchResponse::default();
+        }
+
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+        let mut required: Vec<HashSet<usize>> = Vec::new();
+        let mut excluded: HashSet<usize> = HashSet::new();
+        let mut highlight_terms: HashMap<Language, HashSet<String>> = HashMap::new();
+        for (occur, text) in &clauses {
+            let mut clause_docs = HashSet::new();
+            for (language, analyzer) in &self.analyzers {
+                let terms: Vec<String> = analyzer.analyze(text).into_iter().map(|t| t.text).collect();
+                let matches = self.match_terms(*language, &terms);
+                clause_docs.extend(matches.keys().copied());
+                match occur {
+                    Occur::MustNot => {}
+                    _ => {
+                        for (doc, score) in matches {
+                            *scores.entry(doc).or_default() += score;
+                        }
+                        highlight_terms.entry(*language).or_default().extend(terms);
+                    }
+                }
+            }
+            match occur {
+                Occur::Should => {}
+                Occur::Must => required.push(clause_docs),
+                Occur::MustNot => excluded.extend(clause_docs),
+            }
+        }
+
+        let mut matched: Vec<(usize, f32)> = scores
+            .into_iter()
+            .filter(|(doc, _)| !excluded.contains(doc))
+            .filter(|(doc, _)| required.iter().all(|docs| docs.contains(doc)))
+            .filter(|(doc, _)| self.passes_filters(&self.artifact.docs[*doc], &request.filters))
+            .collect();
+
+        let mut facets = BTreeMap::new();
+        for &facet in &request.facets {
+            facets.insert(facet.name().to_string(), self.facet_counts(&matched, facet));
+        }
+
+        self.sort(&mut matched, request.sort);
+        let total = matched.len();
+        let mut hits: Vec<SearchHit> = matched
+            .into_iter()
+            .skip(request.offset)
+            .take(request.limit)
+            .filter(|(_, score)| request.min_score.is_none_or(|min_score| *score >= min_score))
+            .map(|(doc, score)| self.hit(doc, score, request, &highlight_terms))
+            .collect();
+        if request.limit == 0 {
+            hits.clear();
+        }
+
+        SearchResponse {
+            hits,
+            total,
+            facets,
+            took_ms: 0,
+            suggestion: None,
+        }
+    }
+
+    /// 同时包含全部词项的文档及其得分
+    fn match_terms(&self, language: Language, terms: &[String]) -> HashMap<usize, f32> {
+        let mut matches: Option<HashMap<usize, f32>> = None;
+        for term in terms {
+            let Some(postings) = self.artifact.postings_of(language, term) else {
+                return HashMap::new();
+            };
+            let idf = [0, 1].map(|field| {
+                let doc_freq = postings.iter().filter(|p| field_tf(p, field) > 0).count();
+                self.idf(doc_freq)
+            });
+            let term_scores: HashMap<usize, f32> = postings
+                .iter()
+                .map(|posting| {
+                    let doc = &self.artifact.docs[posting.doc()];
+                    let score: f32 = [0, 1]
+                        .into_iter()
+                        .map(|field| {
+                            self.bm25(language, field, idf[field], field_tf(posting, field), doc.field_lens[field])
+                        })
+                        .sum();
+                    (posting.doc(), score)
+                })
+                .collect();
+            matches = Some(match matches {
+                None => term_scores,
+                Some(previous) => previous
+                    .into_iter()
+                    .filter_map(|(doc, score)| term_scores.get(&doc).map(|s| (doc, score + s)))
+                    .collect(),
+            });
+        }
+        matches.unwrap_or_default()
+    }
+
+    fn idf(&self, doc_freq: usize) -> f32 {
+        let doc_count = self.artifact.docs.len() as f32;
+        let doc_freq = doc_freq as f32;
+        (1.0 + (doc_count - doc_freq + 0.5) / (doc_freq + 0.5)).ln()
+    }
+
+    fn bm25(&self, language: Language, field: usize, idf: f32, tf: u32, field_len: u32) -> f32 {
+        if tf == 0 {
+            return 0.0;
+        }
+        // 与 tantivy 一致，平均长度按全部文档计算（其他语言的文档该字段长度为 0）
+        let total_tokens = self
+            .artifact
+            .field_tokens
+            .get(language.code())
+            .map_or(0, |tokens| tokens[field]);
+        let average_len = (total_tokens as f32 / self.artifact.docs.len().max(1) as f32).max(1.0);
+        let tf = tf as f32;
+        let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * field_len as f32 / average_len);
+        idf * (BM25_K1 + 1.0) * tf / (tf + norm)
+    }
+
+    fn passes_filters(&self, doc: &ArtifactDoc, filters: &[Filter]) -> bool {
+        filters.iter().all(|filter| match filter {
+            Filter::Tag(tag) => doc.tags.iter().any(|t| t == tag),
+            Filter::Lang(language) => doc.lang == language.code(),
+        })
+    }
+
+    fn facet_counts(&self, matched: &[(usize, f32)], facet: FacetField) -> Vec<FacetCount> {
+        let mut counts: BTreeMap<&str, u64> = BTreeMap::new();
+        for &(doc, _) in matched {
+            let doc = &self.artifact.docs[doc];
+            match facet {
+                FacetField::Tags => {
+                    for tag in &doc.tags {
+                        *counts.entry(tag).or_default() += 1;
+                    }
+                }
+                FacetField::Lang => *counts.entry(&doc.lang).or_default() += 1,
+            }
+        }
+        let mut values: Vec<FacetCount> = counts
+            .into_iter()
+            .map(|(value, count)| FacetCount { value: value.to_string(), count })
+            .collect();
+        values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        values
+    }
+
+    /// 排序规则与服务端一致：没有日期的文档排在最后，日期相同时按得分
+    fn sort(&self, matched: &mut [(usize, f32)], sort: SortOrder) {
+        let by_score = |a: &(usize, f32), b: &(usize, f32)| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0));
+        match sort {
+            // 静态索引没有点击热度，按相关度排序
+            SortOrder::Relevance | SortOrder::Popular => matched.sort_by(by_score),
+            SortOrder::Newest | SortOrder::Oldest => {
+                let newest = sort == SortOrder::Newest;
+                matched.sort_by(|a, b| {
+                    let date_a = self.artifact.docs[a.0].date.as_deref();
+                    let date_b = self.artifact.docs[b.0].date.as_deref();
+                    let by_date = match (date_a, date_b) {
+                        (Some(x), Some(y)) if newest => y.cmp(x),
+                        (Some(x), Some(y)) => x.cmp(y),
+                        (a, b) => b.is_some().cmp(&a.is_some()),
+                    };
+                    by_date.then_with(|| by_score(a, b))
+                });
+            }
+        }
+    }
+
+    fn hit(
+        &self,
+        doc_id: usize,
+        score: f32,
+        request: &SearchRequest,
+        highlight_terms: &HashMap<Language, HashSet<String>>,
+    ) -> SearchHit {
+        let doc = &self.artifact.docs[doc_id];
+        let language = Language::from_code(&doc.lang).unwrap_or_default();
+        let highlight = request.highlight.and_then(|options| {
+            let analyzer = self.analyzers.iter().find(|(l, _)| *l == language).map(|(_, a)| a)?;
+            let terms = highlight_terms.get(&language)?;
+            highlight_excerpt(analyzer, &doc.excerpt, terms, options)
+        });
+        SearchHit {
+            score,
+            path: doc.path.clone(),
+            title: request.wants(HitField::Title).then(|| doc.title.clone()),
+            excerpt: request.wants(HitField::Excerpt).then(|| doc.excerpt.clone()),
+            tags: request.wants(HitField::Tags).then(|| doc.tags.clone()),
+            lang: request.wants(HitField::Lang).then(|| doc.lang.clone()),
+            date: request.wants(HitField::Date).then(|| doc.date.clone()).flatten(),
+            highlight,
+            ..SearchHit::default()
+        }
+    }
+}
+
+fn field_tf(posting: &crate::artifact::Posting, field: usize) -> u32 {
+    match field {
+        0 => posting.title_tf(),
+        _ => posting.content_tf(),
+    }
+}
+
+/// 把查询切分为子句；布尔运算符、字段名和邻近距离被忽略
+fn parse_clauses(query: &str) -> Vec<(Occur, String)> {
+    split_query(query)
+        .into_iter()
+        .filter(|token| !matches!(*token, "AND" | "OR" | "NOT"))
+        .filter_map(|token| {
+            let (occur, token) = match token.as_bytes().first() {
+                Some(b'+') => (Occur::Must, &token[1..]),
+                Some(b'-') => (Occur::MustNot, &token[1..]),
+                _ => (Occur::Should, token),
+            };
+            let token = match token.rfind('~') {
+                Some(i) if token[i + 1..].chars().all(|c| c.is_ascii_digit()) => &token[..i],
+                _ => token,
+            };
+            let text = token.trim_matches(|c| matches!(c, '"' | '(' | ')'));
+            let text = match text.split_once(':') {
+                Some((_, value)) if !text.starts_with('"') => value,
+                _ => text,
+            };
+            (!text.trim().is_empty()).then(|| (occur, text.to_string()))
+        })
+        .collect()
+}
+
+/// 在摘要中用 `<b>` 标出查询词，与服务端高亮的格式相同；没有命中时返回 `None`
+fn highlight_excerpt(
+    analyzer: &Analyzer,
+    excerpt: &str,
+    terms: &HashSet<String>,
+    options: HighlightOptions,
+) -> Option<String> {
+    // 合并重叠的词项（中文二元组）
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for token in analyzer.analyze(excerpt) {
+        if !terms.contains(&token.text) {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(last) if token.offset_from <= last.1 => last.1 = last.1.max(token.offset_to),
+            _ => ranges.push((token.offset_from, token.offset_to)),
+        }
+    }
+    let &(first_from, _) = ranges.first()?;
+
+    // 第一个命中在截取范围之外时，从命中处开始截取
+    let start = if excerpt[..first_from].chars().count() + 1 > options.max_chars {
+        first_from
+    } else {
+        0
+    };
+    let end = excerpt[start..]
+        .char_indices()
+        .nth(options.max_chars)
+        .map_or(excerpt.len(), |(i, _)| start + i);
+
+    let mut html = String::new();
+    let mut cursor = start;
+    for (from, to) in ranges {
+        if from < start || to > end {
+            continue;
+        }
+        html.push_str(&escape_html(&excerpt[cursor..from]));
+        html.push_str("<b>");
+        html.push_str(&escape_html(&excerpt[from..to]));
+        html.push_str("</b>");
+        cursor = to;
+    }
+    html.push_str(&escape_html(&excerpt[cursor..end]));
+    Some(html)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact::ArtifactBuilder;
+    use crate::AnalysisConfig;
+
+    fn sample() -> StaticSearch {
+        let mut builder = ArtifactBuilder::new(AnalysisConfig::default());
+        let docs = [
+            ("a.md", "搜索引擎入门", "zh", "倒排索引是搜索引擎的核心", "2024-01-01", "rust"),
+            ("b.md", "Indexing Errors", "en", "How errors are indexed & <reported>", "2023-01-01", "rust"),
+            ("c.md", "Error Handling", "en", "Handling errors in services", "", "go"),
+        ];
+        for (path, title, lang, content, date, tag) in docs {
+            builder.add_document(
+                ArtifactDoc {
+                    path: path.to_string(),
+                    title: title.to_string(),
+                    excerpt: content.to_string(),
+                    tags: vec![tag.to_string()],
+                    lang: lang.to_string(),
+                    date: (!date.is_empty()).then(|| date.to_string()),
+                    field_lens: [0, 0],
+                },
+                content,
+            );
+        }
+        StaticSearch::new(builder.build()).unwrap()
+    }
+
+    fn paths(response: &SearchResponse) -> Vec<&str> {
+        response.hits.iter().map(|hit| hit.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_query_syntax() {
+        let search = sample();
+        let response = search.search(&SearchRequest::new("errors"));
+        assert_eq!(response.total, 2);
+        assert_eq!(paths(&search.search(&SearchRequest::new("error -handling"))), ["b.md"]);
+        assert_eq!(paths(&search.search(&SearchRequest::new("error +handling"))), ["c.md"]);
+        assert_eq!(paths(&search.search(&SearchRequest::new("\"搜索引擎\""))), ["a.md"]);
+        // 二元组需全部出现
+        assert_eq!(search.search(&SearchRequest::new("引擎搜")).total, 0);
+        assert_eq!(search.search(&SearchRequest::new("-error")).total, 0);
+    }
+
+    #[test]
+    fn test_filters_sort_and_facets() {
+        let search = sample();
+        let request = SearchRequest::builder("error 搜索")
+            .tag("rust")
+            .sort(SortOrder::Newest)
+            .facet(FacetField::Lang)
+            .build();
+        let response = search.search(&request);
+        assert_eq!(paths(&response), ["a.md", "b.md"]);
+        assert_eq!(response.facets["lang"].len(), 2);
+
+        let request = SearchRequest::builder("error").sort(SortOrder::Oldest).build();
+        assert_eq!(paths(&search.search(&request)), ["b.md", "c.md"]);
+    }
+
+    #[test]
+    fn test_highlight_and_shards() {
+        let mut artifact = sample().artifact;
+        let shards = artifact.split_shards();
+        let mut search = StaticSearch::new(artifact).unwrap();
+        assert_eq!(search.search(&SearchRequest::new("indexed")).total, 0);
+        assert_eq!(search.missing_shards("indexed 搜索"), ["i", "u64"]);
+
+        for key in search.missing_shards("indexed") {
+            search.add_shard(&key, shards[&key].clone()).unwrap();
+        }
+        assert!(search.missing_shards("indexed").is_empty());
+        let request = SearchRequest::builder("indexed").highlight(true).build();
+        let response = search.search(&request);
+        assert_eq!(
+            response.hits[0].highlight.as_deref(),
+            Some("How errors are <b>indexed</b> &amp; &lt;reported&gt;")
+        );
+    }
+}
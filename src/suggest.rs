@@ -0,0 +1,234 @@
+//! 搜索建议（输入即搜索的自动补全）
+//!
+//! 建议来源于文档标题与标签，按小写键排序存放，前缀查找只需一次二分定位。
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 默认返回的建议数量
+pub const DEFAULT_SUGGEST_LIMIT: usize = 8;
+/// 单个标题词参与建议的最小字符数
+const MIN_TERM_CHARS: usize = 2;
+
+/// 建议来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionKind {
+    /// 完整的文章标题
+    Title,
+    /// 文章标签
+    Tag,
+    /// 标题中的单词
+    Term,
+}
+
+/// 一条补全建议
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Suggestion {
+    /// 补全后的文本
+    pub text: String,
+    /// 建议来源
+    pub kind: SuggestionKind,
+    /// 权重（包含该词的文档数）
+    pub weight: u64,
+}
+
+struct SuggestEntry {
+    key: String,
+    text: String,
+    kind: SuggestionKind,
+    weight: u64,
+}
+
+/// 前缀索引
+#[derive(Default)]
+pub struct SuggestIndex {
+    entries: Vec<SuggestEntry>,
+}
+
+/// 前缀索引构建器
+#[derive(Default)]
+pub struct SuggestIndexBuilder {
+    entries: HashMap<String, SuggestEntry>,
+}
+
+impl SuggestIndexBuilder {
+    /// 加入一篇文档的标题和标签
+    pub fn add_document(&mut self, title: &str, tags: &[String]) {
+        let title = title.trim();
+        let candidates = std::iter::once((title, SuggestionKind::Title))
+            .chain(tags.iter().map(|t| (t.trim(), SuggestionKind::Tag)))
+            .chain(
+                split_words(title)
+                    .filter(|w| w.chars().count() >= MIN_TERM_CHARS)
+                    .map(|w| (w, SuggestionKind::Term)),
+            )
+            .filter(|(text, _)| !text.is_empty());
+
+        // 同一篇文档中重复出现的文本只计一次，并保留最具体的来源
+        let mut doc_entries: HashMap<String, (&str, SuggestionKind)> = HashMap::new();
+        for (text, kind) in candidates {
+            let slot = doc_entries.entry(normalize(text)).or_insert((text, kind));
+            if kind < slot.1 {
+                *slot = (text, kind);
+            }
+        }
+
+        for (key, (text, kind)) in doc_entries {
+            let entry = self.entries.entry(key.clone()).or_insert_with(|| SuggestEntry {
+                key,
+                text: text.to_string(),
+                kind,
+                weight: 0,
+            });
+            entry.weight += 1;
+            if kind < entry.kind {
+                entry.kind = kind;
+                entry.text = text.to_string();
+            }
+        }
+    }
+
+    /// 生成前缀索引
+    pub fn build(self) -> SuggestIndex {
+        let mut entries: Vec<SuggestEntry> = self.entries.into_values().collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        SuggestIndex { entries }
+    }
+}
+
+impl SuggestIndex {
+    /// 创建构建器
+    pub fn builder() -> SuggestIndexBuilder {
+        SuggestIndexBuilder::default()
+    }
+
+    /// 索引中的建议条目数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 索引是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 查找以 `input` 为前缀的建议
+    ///
+    /// 整个输入没有足够匹配时，会对最后一个单词做补全并保留前面已输入的部分，
+    /// 例如 `rust asy` 可以补全为 `rust async`。
+    ///
+    /// # Arguments
+    /// * `input` - 用户已输入的文本
+    /// * `limit` - 最多返回的建议数量
+    pub fn lookup(&self, input: &str, limit: usize) -> Vec<Suggestion> {
+        let prefix = normalize(input);
+        if prefix.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let mut results: Vec<Suggestion> = self
+            .ranked(&prefix)
+            .into_iter()
+            .take(limit)
+            .map(|entry| Suggestion {
+                text: entry.text.clone(),
+                kind: entry.kind,
+                weight: entry.weight,
+            })
+            .collect();
+
+        if results.len() < limit {
+            if let Some((head, last)) = prefix.rsplit_once(' ') {
+                for entry in self.ranked(last) {
+                    if results.len() >= limit {
+                        break;
+                    }
+                    if entry.kind == SuggestionKind::Title {
+                        continue;
+                    }
+                    let text = format!("{} {}", head, entry.key);
+                    if results.iter().any(|s| normalize(&s.text) == text) {
+                        continue;
+                    }
+                    results.push(Suggestion {
+                        text,
+                        kind: entry.kind,
+                        weight: entry.weight,
+                    });
+                }
+            }
+        }
+
+        results
+    }
+
+    /// 按权重降序、长度升序排列所有前缀匹配项
+    fn ranked(&self, prefix: &str) -> Vec<&SuggestEntry> {
+        let start = self.entries.partition_point(|e| e.key.as_str() < prefix);
+        let mut matches: Vec<&SuggestEntry> = self.entries[start..]
+            .iter()
+            .take_while(|e| e.key.starts_with(prefix))
+            .collect();
+        matches.sort_by(|a, b| {
+            b.weight
+                .cmp(&a.weight)
+                .then_with(|| a.key.chars().count().cmp(&b.key.chars().count()))
+                .then_with(|| a.kind.cmp(&b.kind))
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        matches
+    }
+}
+
+/// 小写化并合并连续空白
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn split_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_index() -> SuggestIndex {
+        let mut builder = SuggestIndex::builder();
+        builder.add_document("Rust Async Basics", &["rust".to_string(), "async".to_string()]);
+        builder.add_document("Rust Error Handling", &["rust".to_string()]);
+        builder.add_document("测试文章", &["test".to_string()]);
+        builder.build()
+    }
+
+    #[test]
+    fn test_prefix_ranked_by_weight() {
+        let index = sample_index();
+        let results = index.lookup("ru", 5);
+        assert_eq!(results[0].text, "rust");
+        assert_eq!(results[0].kind, SuggestionKind::Tag);
+        assert_eq!(results[0].weight, 2);
+        assert!(results.iter().any(|s| s.text == "Rust Async Basics"));
+    }
+
+    #[test]
+    fn test_completes_last_word() {
+        let index = sample_index();
+        let results = index.lookup("rust asy", 5);
+        assert_eq!(results[0].text, "Rust Async Basics");
+        assert!(results.iter().any(|s| s.text == "rust async"));
+    }
+
+    #[test]
+    fn test_cjk_prefix_and_limit() {
+        let index = sample_index();
+        assert_eq!(index.lookup("测试", 5)[0].text, "测试文章");
+        assert_eq!(index.lookup("r", 1).len(), 1);
+        assert!(index.lookup("  ", 5).is_empty());
+    }
+}
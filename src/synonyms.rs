@@ -0,0 +1,177 @@
+//! 同义词与缩写扩展
+//!
+//! 同义词文件为 TOML 格式，例如：
+//!
+//! ```toml
+//! # 双向规则：同组内的词互为同义词
+//! two_way = [
+//!     ["k8s", "kubernetes"],
+//!     ["数据库", "db", "database"],
+//! ]
+//!
+//! # 单向规则：左侧的词扩展为右侧的词，反之不成立
+//! [one_way]
+//! js = ["javascript"]
+//! ```
+//!
+//! 扩展发生在 `QueryParser` 之前，`k8s deploy` 会被改写为
+//! `(k8s OR kubernetes) deploy`。
+
+use crate::query::{is_plain_term, split_query};
+use crate::SearchResult;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 同义词文件
+#[derive(Debug, Default, Deserialize)]
+struct SynonymFile {
+    #[serde(default)]
+    two_way: Vec<Vec<String>>,
+    #[serde(default)]
+    one_way: HashMap<String, Vec<String>>,
+}
+
+/// 同义词表
+#[derive(Debug, Default)]
+pub struct SynonymMap {
+    /// 小写的源词 -> 扩展词（不含源词本身）
+    rules: HashMap<String, Vec<String>>,
+}
+
+impl SynonymMap {
+    /// 从 TOML 文本解析同义词表
+    pub fn from_toml(text: &str) -> SearchResult<Self> {
+        let file: SynonymFile = toml::from_str(text)?;
+        let mut map = SynonymMap::default();
+
+        for group in &file.two_way {
+            for source in group {
+                map.add_rule(source, group);
+            }
+        }
+        for (source, targets) in &file.one_way {
+            map.add_rule(source, targets);
+        }
+
+        Ok(map)
+    }
+
+    /// 从文件加载同义词表
+    pub fn load(path: &Path) -> SearchResult<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_toml(&text)
+    }
+
+    fn add_rule(&mut self, source: &str, targets: &[String]) {
+        let key = source.trim().to_lowercase();
+        if key.is_empty() {
+            return;
+        }
+
+        let expansions = self.rules.entry(key.clone()).or_default();
+        for target in targets {
+            let target = target.trim();
+            if target.is_empty() || target.to_lowercase() == key {
+                continue;
+            }
+            if !expansions.iter().any(|t| t.eq_ignore_ascii_case(target)) {
+                expansions.push(target.to_string());
+            }
+        }
+    }
+
+    /// 规则中的源词数量
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// 同义词表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 扩展查询字符串
+    ///
+    /// 只扩展普通词项；短语、字段限定、排除项和布尔运算符保持原样。
+    /// 没有命中任何规则时返回原查询。
+    pub fn expand(&self, query: &str) -> String {
+        if self.rules.is_empty() {
+            return query.to_string();
+        }
+
+        let mut expanded_any = false;
+        let tokens: Vec<String> = split_query(query)
+            .into_iter()
+            .map(|token| {
+                let expansions = if is_plain_term(token) {
+                    self.rules.get(&token.to_lowercase())
+                } else {
+                    None
+                };
+                match expansions {
+                    Some(expansions) if !expansions.is_empty() => {
+                        expanded_any = true;
+                        let alternatives: Vec<String> = std::iter::once(token.to_string())
+                            .chain(expansions.iter().map(|t| quote_if_needed(t)))
+                            .collect();
+                        format!("({})", alternatives.join(" OR "))
+                    }
+                    _ => token.to_string(),
+                }
+            })
+            .collect();
+
+        if expanded_any {
+            tokens.join(" ")
+        } else {
+            query.to_string()
+        }
+    }
+}
+
+fn quote_if_needed(term: &str) -> String {
+    if term.contains(|c: char| c.is_whitespace() || "\":()[]{}^~*+-\\".contains(c)) {
+        format!("\"{}\"", term.replace('"', ""))
+    } else {
+        term.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+        two_way = [
+            ["k8s", "Kubernetes"],
+            ["数据库", "DB", "database"],
+        ]
+
+        [one_way]
+        js = ["JavaScript"]
+        ml = ["machine learning"]
+    "#;
+
+    #[test]
+    fn test_two_way_and_one_way_rules() -> SearchResult<()> {
+        let map = SynonymMap::from_toml(SAMPLE)?;
+        assert_eq!(map.expand("k8s deploy"), "(k8s OR Kubernetes) deploy");
+        assert_eq!(map.expand("kubernetes"), "(kubernetes OR k8s)");
+        assert_eq!(map.expand("DB"), "(DB OR 数据库 OR database)");
+        assert_eq!(map.expand("JS"), "(JS OR JavaScript)");
+        // 单向规则不反向扩展
+        assert_eq!(map.expand("javascript"), "javascript");
+        assert_eq!(map.expand("ml"), "(ml OR \"machine learning\")");
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_syntax_is_preserved() -> SearchResult<()> {
+        let map = SynonymMap::from_toml(SAMPLE)?;
+        assert_eq!(map.expand("\"k8s  operator\"~2 js"), "\"k8s  operator\"~2 (js OR JavaScript)");
+        assert_eq!(map.expand("title:k8s -js AND go"), "title:k8s -js AND go");
+        assert!(SynonymMap::from_toml("two_way = 1").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+//! 把 `analysis` 中的分析器注册为 tantivy 分词器
+
+use crate::analysis::{AnalysisConfig, Analyzer};
+use std::sync::Arc;
+use tantivy::tokenizer::{BoxTokenStream, TextAnalyzer, Token, TokenStream, Tokenizer};
+use tantivy::Index;
+
+/// CJK 分析器的注册名
+pub(crate) const CJK_TOKENIZER: &str = "cjk";
+/// 英文分析器的注册名
+pub(crate) const ENGLISH_TOKENIZER: &str = "en_stem";
+
+/// 在索引上注册所有自定义分词器
+pub(crate) fn register(index: &Index, config: &AnalysisConfig) {
+    let tokenizers = index.tokenizers();
+    tokenizers.register(CJK_TOKENIZER, AnalyzerTokenizer::text_analyzer(Analyzer::Cjk));
+    tokenizers.register(
+        ENGLISH_TOKENIZER,
+        AnalyzerTokenizer::text_analyzer(Analyzer::english(&config.english)),
+    );
+}
+
+#[derive(Clone)]
+struct AnalyzerTokenizer {
+    analyzer: Arc<Analyzer>,
+}
+
+impl AnalyzerTokenizer {
+    fn text_analyzer(analyzer: Analyzer) -> TextAnalyzer {
+        TextAnalyzer::from(AnalyzerTokenizer {
+            analyzer: Arc::new(analyzer),
+        })
+    }
+}
+
+impl Tokenizer for AnalyzerTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        let tokens = self
+            .analyzer
+            .analyze(text)
+            .into_iter()
+            .map(|token| Token {
+                offset_from: token.offset_from,
+                offset_to: token.offset_to,
+                position: token.position,
+                text: token.text,
+                position_length: 1,
+            })
+            .collect();
+        BoxTokenStream::from(VecTokenStream { tokens, next: 0 })
+    }
+}
+
+struct VecTokenStream {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl TokenStream for VecTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.next < self.tokens.len() {
+            self.next += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.next - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.next - 1]
+    }
+}
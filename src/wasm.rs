@@ -0,0 +1,79 @@
+//! 浏览器端的 JavaScript 接口
+//!
+//! 使用 `wasm-pack build --target web -- --no-default-features --features wasm`
+//! 构建，生成的 `blog_search_service.js` 导出 `StaticSearchEngine`：
+//!
+//! ```js
+//! import init, { StaticSearchEngine } from "./pkg/blog_search_service.js";
+//!
+//! await init();
+//! const engine = new StaticSearchEngine(await (await fetch("search/manifest.json")).text());
+//! for (const key of engine.missingShards(q)) {
+//!     engine.addShard(key, await (await fetch(`search/shard-${key}.json`)).text());
+//! }
+//! const response = engine.search({ q, page: 1, size: 10, highlight: true });
+//! ```
+//!
+//! `search` 接受与 `/api/search` 相同的参数，按默认的 [`RequestLimits`] 校验后
+//! 返回相同结构的响应。
+
+use crate::http::{SearchParams, SearchResponseBody};
+use crate::{RequestLimits, StaticSearch};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// 加载静态索引的搜索引擎
+#[wasm_bindgen]
+pub struct StaticSearchEngine {
+    inner: StaticSearch,
+}
+
+#[wasm_bindgen]
+impl StaticSearchEngine {
+    /// 从 `manifest.json` 的内容创建
+    #[wasm_bindgen(constructor)]
+    pub fn new(manifest: &str) -> Result<StaticSearchEngine, JsError> {
+        Ok(StaticSearchEngine {
+            inner: StaticSearch::from_json(manifest)?,
+        })
+    }
+
+    /// 索引中的文档数
+    #[wasm_bindgen(getter, js_name = docCount)]
+    pub fn doc_count(&self) -> usize {
+        self.inner.doc_count()
+    }
+
+    /// 执行查询前还需要下载的分片键
+    #[wasm_bindgen(js_name = missingShards)]
+    pub fn missing_shards(&self, query: &str) -> Vec<String> {
+        self.inner.missing_shards(query)
+    }
+
+    /// 合并 `shard-<key>.json` 的内容
+    #[wasm_bindgen(js_name = addShard)]
+    pub fn add_shard(&mut self, key: &str, shard: &str) -> Result<(), JsError> {
+        self.inner.add_shard(key, serde_json::from_str(shard)?)?;
+        Ok(())
+    }
+
+    /// 执行搜索
+    pub fn search(&self, options: JsValue) -> Result<JsValue, JsError> {
+        let mut params: SearchParams = serde_wasm_bindgen::from_value(options)?;
+        params.validate(&RequestLimits::default())?;
+        let request = params.to_request()?;
+
+        let started = js_sys::Date::now();
+        let mut outcome = self.inner.search(&request);
+        outcome.took_ms = (js_sys::Date::now() - started) as u64;
+
+        let response = SearchResponseBody {
+            query: params.q,
+            page: params.page,
+            size: params.size,
+            outcome,
+        };
+        let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+        Ok(response.serialize(&serializer)?)
+    }
+}
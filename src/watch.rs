@@ -0,0 +1,55 @@
+//! 文章目录的文件监视
+//!
+//! 本地写作时保存文章后立即能搜到：[`ContentWatcher`] 递归监视文章目录，编辑器保存时的
+//! 一连串事件（写临时文件、改名、多次写入）在 [`DEFAULT_DEBOUNCE`] 内合并为一批，
+//! 只把变更的 Markdown 文件交给回调，由回调调用 [`SearchEngine::sync_documents`](crate::SearchEngine::sync_documents)
+//! 或提交同步任务；文件是否已删除由同步时检查磁盘决定。
+
+use crate::SearchResult;
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 默认的合并时间窗口
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 文章目录的监视器，释放后停止监视
+pub struct ContentWatcher {
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+impl ContentWatcher {
+    /// 开始监视 `dir` 及其子目录
+    ///
+    /// 每批变更在监视线程中调用一次 `on_change`，路径去重排序，并与 `dir` 拼接的形式一致
+    /// （与 [`SearchEngine::reindex`](crate::SearchEngine::reindex) 写入索引的路径相同）；
+    /// 监视后端出错时以错误调用，监视继续进行。
+    pub fn start<F>(dir: &Path, debounce: Duration, mut on_change: F) -> SearchResult<Self>
+    where
+        F: FnMut(SearchResult<Vec<PathBuf>>) + Send + 'static,
+    {
+        let root = dir.to_path_buf();
+        // 监视后端报告的可能是绝对路径，换回 `dir` 下的路径才能对应到索引中的文档
+        let canonical_root = dir.canonicalize()?;
+        let mut debouncer = new_debouncer(debounce, move |result: DebounceEventResult| match result {
+            Ok(events) => {
+                let paths: BTreeSet<PathBuf> = events
+                    .into_iter()
+                    .filter(|event| event.path.extension().is_some_and(|ext| ext == "md"))
+                    .map(|event| match event.path.strip_prefix(&canonical_root) {
+                        Ok(relative) => root.join(relative),
+                        Err(_) => event.path,
+                    })
+                    .collect();
+                if !paths.is_empty() {
+                    on_change(Ok(paths.into_iter().collect()));
+                }
+            }
+            Err(e) => on_change(Err(e.into())),
+        })?;
+        debouncer.watcher().watch(dir, RecursiveMode::Recursive)?;
+        Ok(ContentWatcher { _debouncer: debouncer })
+    }
+}